@@ -0,0 +1,225 @@
+use crate::config::{
+    load_config, AppIconConfig, Diagnostic, Diagnostics, ExecConfig, ItemAction, ItemKind, Severity,
+};
+use crate::gamepad_manager::ButtonChord;
+use crate::get_color;
+use crate::icon_theme::IconLookup;
+use crate::launcher::{item_wrappers, Executable, RestartOptions};
+use crate::logind::{Logind, PowerAction};
+use crate::program_path::find_program;
+
+use std::path::Path;
+
+// Matches `ScreenLayout.default-icon-size`, the UI is not created while checking the config
+const DEFAULT_ICON_SIZE: f32 = 128.0;
+
+/// Validates the config file, its icons and programs and prints the report to stdout.
+/// Returns `true` if no errors are found.
+pub fn check_config(config_path: &Path) -> bool {
+    let mut diagnostics = Diagnostics::default();
+    let mut resolved = Vec::new();
+
+    if let Some(mut config) = load_config(config_path, &mut diagnostics, &mut Vec::new()) {
+        let icon_size = config
+            .layout
+            .as_ref()
+            .and_then(|layout| layout.icon_size)
+            .unwrap_or(DEFAULT_ICON_SIZE);
+        let style = config.style.take().unwrap_or_default();
+        for color in [&style.bg_color, &style.panel_color, &style.text_color] {
+            _ = get_color(color, &mut diagnostics);
+        }
+
+        let force_quit = config.force_quit.take().unwrap_or_default();
+        _ = ButtonChord::from_config(&force_quit, &mut diagnostics);
+
+        let launch = config.launch.take().unwrap_or_default();
+        check_hooks("Launch", &launch.pre_exec, &mut diagnostics);
+        check_hooks("Launch", &launch.post_exec, &mut diagnostics);
+
+        let mut icons = IconLookup::new(style.icon_theme.as_deref(), icon_size, 1.0);
+
+        let mut logind = None;
+        let mut items = Vec::new();
+        flatten_items(config.take_items(), &mut items);
+
+        for item in items {
+            let icon = icons.find_icon(&item.icon).filter(|path| path.is_file());
+            if icon.is_none() {
+                let message = format!("Item `{}`: icon `{}` is not found", item.name, item.icon);
+                diagnostics.push(Diagnostic::error(message));
+            }
+
+            if item.kind == ItemKind::Folder {
+                resolved.push((item.name, icon, Some("folder".to_string())));
+                continue;
+            }
+
+            let program = match (item.action, Executable::new(&item.exec, item.shell)) {
+                (Some(action), _) => {
+                    check_action(&item.name, action, &mut logind, &mut diagnostics);
+                    Some(format!("{} action", action))
+                }
+                (None, Ok(exec)) => find_program(&exec.program)
+                    .map(|path| path.display().to_string())
+                    .or_else(|| {
+                        let message = format!(
+                            "Item `{}`: program `{}` is not found",
+                            item.name, exec.program
+                        );
+                        diagnostics.push(Diagnostic::error(message));
+                        None
+                    }),
+                (None, Err(error)) => {
+                    let message = format!("Item `{}`: failed to parse exec: {}", item.name, error);
+                    diagnostics.push(Diagnostic::error(message));
+                    None
+                }
+            };
+
+            if let Some(cwd) = item.cwd.as_ref().filter(|cwd| !Path::new(cwd).is_dir()) {
+                let message = format!("Item `{}`: directory `{}` is not found", item.name, cwd);
+                diagnostics.push(Diagnostic::error(message));
+            }
+
+            for name in item_wrappers(&item, &launch) {
+                match Executable::default().wrap([name], &config.wrappers) {
+                    Ok(wrapper)
+                        if !wrapper.program.is_empty()
+                            && find_program(&wrapper.program).is_none() =>
+                    {
+                        let message = format!(
+                            "Item `{}`: wrapper `{}` program `{}` is not found",
+                            item.name, name, wrapper.program
+                        );
+                        diagnostics.push(Diagnostic::error(message));
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        let message = format!("Item `{}`: {}", item.name, error);
+                        diagnostics.push(Diagnostic::error(message));
+                    }
+                }
+            }
+
+            let context = format!("Item `{}`", item.name);
+            check_hooks(&context, &item.pre_exec, &mut diagnostics);
+            check_hooks(&context, &item.post_exec, &mut diagnostics);
+
+            resolved.push((item.name, icon, program));
+        }
+
+        let kiosk = config.kiosk.take().unwrap_or_default();
+        if let Some(item) = &kiosk.autostart {
+            if !resolved.iter().any(|(name, _, _)| name == item) {
+                let message = format!("Kiosk item `{}` is not found", item);
+                diagnostics.push(Diagnostic::error(message));
+            }
+        }
+        _ = RestartOptions::from_config(&kiosk, &mut diagnostics);
+    }
+
+    println!("Config: {}", config_path.display());
+
+    if !resolved.is_empty() {
+        println!("\nItems:");
+    }
+    for (name, icon, program) in &resolved {
+        let status = if icon.is_some() && program.is_some() {
+            "ok"
+        } else {
+            "error"
+        };
+        println!("  [{}] {}", status, name);
+        println!("      icon: {}", display_path(icon.as_deref()));
+        println!("      program: {}", program.as_deref().unwrap_or("-"));
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    if diagnostics.len() > 0 {
+        println!("\nProblems:");
+    }
+    for diagnostic in diagnostics.iter() {
+        let severity = match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("  {}: {}", severity, diagnostic);
+    }
+
+    println!("\n{} error(s), {} warning(s)", errors, warnings);
+
+    errors == 0
+}
+
+/// Puts items of every folder after the folder itself.
+fn flatten_items(items: Vec<AppIconConfig>, flat: &mut Vec<AppIconConfig>) {
+    for mut item in items {
+        let children = std::mem::take(&mut item.items);
+        flat.push(item);
+        flatten_items(children, flat);
+    }
+}
+
+fn display_path(path: Option<&Path>) -> String {
+    path.map(|path| path.display().to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn check_hooks(context: &str, hooks: &[ExecConfig], diagnostics: &mut Diagnostics) {
+    for hook in hooks {
+        match Executable::new(hook, false) {
+            Ok(exec) if find_program(&exec.program).is_none() => {
+                let message = format!("{}: hook program `{}` is not found", context, exec.program);
+                diagnostics.push(Diagnostic::error(message));
+            }
+            Ok(_) => {}
+            Err(error) => {
+                let message = format!("{}: failed to parse hook: {}", context, error);
+                diagnostics.push(Diagnostic::error(message));
+            }
+        }
+    }
+}
+
+fn check_action(
+    name: &str,
+    action: ItemAction,
+    logind: &mut Option<Result<Logind, String>>,
+    diagnostics: &mut Diagnostics,
+) {
+    // Actions handled by gpcl itself are always available
+    let action = match action {
+        ItemAction::PowerOff => PowerAction::PowerOff,
+        ItemAction::Reboot => PowerAction::Reboot,
+        ItemAction::Suspend => PowerAction::Suspend,
+        ItemAction::Hibernate => PowerAction::Hibernate,
+        ItemAction::Reload
+        | ItemAction::Quit
+        | ItemAction::Settings
+        | ItemAction::Stats
+        | ItemAction::RestartGpcl => return,
+    };
+
+    let result = match logind.get_or_insert_with(Logind::connect) {
+        Ok(logind) => logind.is_available(action),
+        Err(error) => Err(error.clone()),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => {
+            let message = format!("Item `{}`: {:?} action is not available", name, action);
+            diagnostics.push(Diagnostic::warning(message));
+        }
+        Err(error) => {
+            let message = format!("Item `{}`: {}", name, error);
+            diagnostics.push(Diagnostic::warning(message));
+        }
+    }
+}
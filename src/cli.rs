@@ -0,0 +1,196 @@
+use crate::control::Request;
+
+use log::LevelFilter;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: gpcl [OPTIONS] [COMMAND]
+
+Commands:
+  run    Run the launcher (default)
+  check  Validate the config file and exit
+  stats  Print play time of items and exit
+  ctl    Send a command to the running launcher:
+         launch <ITEM>, reload, state, kill
+
+Options:
+  --config <PATH>      Use the config file at PATH
+  --windowed [WxH]     Run in a window instead of fullscreen
+  --log-level <LEVEL>  Set the log level: off, error, warn, info, debug, trace
+  --json               Print recorded play sessions as JSON (stats)
+  --csv                Print recorded play sessions as CSV (stats)
+  -h, --help           Print help
+  -V, --version        Print version";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run,
+    Check,
+    Stats(StatsFormat),
+    Ctl(Request),
+    Help,
+    Version,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatsFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+pub enum WindowMode {
+    Fullscreen,
+    Windowed(Option<(f32, f32)>),
+}
+
+pub struct Args {
+    pub command: Command,
+    pub config: Option<PathBuf>,
+    pub window_mode: WindowMode,
+    pub log_level: Option<LevelFilter>,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut result = Args {
+        command: Command::Run,
+        config: None,
+        window_mode: WindowMode::Fullscreen,
+        log_level: None,
+    };
+
+    let mut command = None;
+    let mut stats_format = None;
+    let mut args = args.into_iter().peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => result.command = Command::Help,
+            "-V" | "--version" => result.command = Command::Version,
+            "--config" => {
+                let path = args.next().ok_or("Missing value for `--config`")?;
+                result.config = Some(path.into());
+            }
+            "--windowed" => {
+                let size = match args.peek() {
+                    Some(value) if !value.starts_with('-') && value.contains('x') => {
+                        Some(parse_size(&args.next().unwrap_or_default())?)
+                    }
+                    _ => None,
+                };
+                result.window_mode = WindowMode::Windowed(size);
+            }
+            "--log-level" => {
+                let level = args.next().ok_or("Missing value for `--log-level`")?;
+                let level = level
+                    .parse()
+                    .map_err(|_| format!("Invalid log level `{}`", level))?;
+                result.log_level = Some(level);
+            }
+            "--json" => stats_format = Some(StatsFormat::Json),
+            "--csv" => stats_format = Some(StatsFormat::Csv),
+            "run" if command.is_none() => command = Some(Command::Run),
+            "check" if command.is_none() => command = Some(Command::Check),
+            "stats" if command.is_none() => command = Some(Command::Stats(StatsFormat::Text)),
+            "ctl" if command.is_none() => command = Some(Command::Ctl(parse_request(&mut args)?)),
+            _ => return Err(format!("Unexpected argument `{}`", arg)),
+        }
+    }
+
+    if let Some(format) = stats_format {
+        match &mut command {
+            Some(Command::Stats(stats_format)) => *stats_format = format,
+            _ => return Err("`--json` and `--csv` are only supported by `stats`".to_string()),
+        }
+    }
+
+    // `--help` and `--version` take precedence over commands
+    if let (Command::Run, Some(command)) = (&result.command, command) {
+        result.command = command;
+    }
+
+    Ok(result)
+}
+
+fn parse_request(args: &mut impl Iterator<Item = String>) -> Result<Request, String> {
+    let command = args.next().ok_or("Missing command for `ctl`")?;
+    match command.as_str() {
+        "launch" => {
+            let item = args.next().ok_or("Missing item name for `launch`")?;
+            Ok(Request::Launch { item })
+        }
+        "reload" => Ok(Request::Reload),
+        "state" => Ok(Request::State),
+        "kill" => Ok(Request::Kill),
+        _ => Err(format!("Unknown ctl command `{}`", command)),
+    }
+}
+
+fn parse_size(value: &str) -> Result<(f32, f32), String> {
+    let error = || format!("Invalid window size `{}`, expected WxH", value);
+
+    let (width, height) = value.split_once('x').ok_or_else(error)?;
+    let width: f32 = width.parse().map_err(|_| error())?;
+    let height: f32 = height.parse().map_err(|_| error())?;
+
+    if width <= 0.0 || height <= 0.0 {
+        return Err(error());
+    }
+
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string())).map(|args| args.command)
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&[]), Ok(Command::Run));
+        assert_eq!(parse(&["check"]), Ok(Command::Check));
+        assert_eq!(parse(&["stats"]), Ok(Command::Stats(StatsFormat::Text)));
+        assert_eq!(parse(&["--help", "check"]), Ok(Command::Help));
+        assert_eq!(
+            parse(&["check", "run"]),
+            Err("Unexpected argument `run`".into())
+        );
+    }
+
+    #[test]
+    fn parses_stats_formats() {
+        assert_eq!(
+            parse(&["stats", "--json"]),
+            Ok(Command::Stats(StatsFormat::Json))
+        );
+        assert_eq!(
+            parse(&["--csv", "stats"]),
+            Ok(Command::Stats(StatsFormat::Csv))
+        );
+
+        let error = "`--json` and `--csv` are only supported by `stats`";
+        assert_eq!(parse(&["--json"]), Err(error.into()));
+        assert_eq!(parse(&["check", "--csv"]), Err(error.into()));
+    }
+
+    #[test]
+    fn parses_ctl_requests() {
+        let launch = Request::Launch {
+            item: "Kodi".to_string(),
+        };
+        assert_eq!(parse(&["ctl", "launch", "Kodi"]), Ok(Command::Ctl(launch)));
+        assert_eq!(parse(&["ctl", "kill"]), Ok(Command::Ctl(Request::Kill)));
+        assert_eq!(parse(&["--version", "ctl", "state"]), Ok(Command::Version));
+
+        assert_eq!(parse(&["ctl"]), Err("Missing command for `ctl`".into()));
+        let error = "Missing item name for `launch`";
+        assert_eq!(parse(&["ctl", "launch"]), Err(error.into()));
+        let error = "Unknown ctl command `stop`";
+        assert_eq!(parse(&["ctl", "stop"]), Err(error.into()));
+        let error = "Unexpected argument `Doom`";
+        assert_eq!(parse(&["ctl", "launch", "Kodi", "Doom"]), Err(error.into()));
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message.into())
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message.into())
+    }
+
+    fn new(severity: Severity, message: String) -> Self {
+        Self {
+            severity,
+            file: None,
+            line: None,
+            column: None,
+            message,
+        }
+    }
+
+    pub fn in_file(mut self, file: &Path) -> Self {
+        self.file = Some(file.to_path_buf());
+        self
+    }
+
+    /// Sets the line and column (both 1-based) of the byte offset in the file contents.
+    pub fn at(mut self, contents: &str, offset: usize) -> Self {
+        let prefix = &contents[..offset.min(contents.len())];
+        let line_start = prefix.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+
+        self.line = Some(prefix.matches('\n').count() + 1);
+        self.column = Some(prefix[line_start..].chars().count() + 1);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+            if let Some(line) = self.line {
+                write!(f, "{}:", line)?;
+            }
+            if let Some(column) = self.column {
+                write!(f, "{}:", column)?;
+            }
+            write!(f, " ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Default)]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        match diagnostic.severity {
+            Severity::Error => log::error!("{}", diagnostic),
+            Severity::Warning => log::warn!("{}", diagnostic),
+        }
+        self.entries.push(diagnostic);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.entries.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
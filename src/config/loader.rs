@@ -0,0 +1,290 @@
+use crate::config::diagnostics::{Diagnostic, Diagnostics};
+use crate::config::{AppIconConfig, Config, StdioConfig};
+use crate::expand::expand_path;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DROP_IN_DIR_EXT: &str = "d";
+const CONFIG_FILE_EXT: &str = "toml";
+
+/// Returns the drop-in directory of the config file, e.g. `gpcl.d` for `gpcl.toml`.
+pub fn drop_in_dir(path: &Path) -> PathBuf {
+    path.with_extension(DROP_IN_DIR_EXT)
+}
+
+/// Loads the config file with its includes and drop-ins merged in, reporting problems into
+/// diagnostics. All files read are added to `files`.
+/// Returns `None` if any of files can't be read or parsed.
+pub fn load_config(
+    path: &Path,
+    diagnostics: &mut Diagnostics,
+    files: &mut Vec<PathBuf>,
+) -> Option<Config> {
+    let mut loader = ConfigLoader {
+        diagnostics,
+        files,
+        include_stack: Vec::new(),
+        failed: false,
+    };
+
+    let mut config = loader.load_with_includes(path).unwrap_or_default();
+
+    for drop_in in list_drop_ins(&drop_in_dir(path)) {
+        if let Some(drop_in_config) = loader.load_with_includes(&drop_in) {
+            config.merge(drop_in_config);
+        }
+    }
+
+    (!loader.failed).then_some(config)
+}
+
+fn list_drop_ins(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == CONFIG_FILE_EXT))
+        .collect();
+
+    files.sort();
+    files
+}
+
+struct ConfigLoader<'a> {
+    diagnostics: &'a mut Diagnostics,
+    files: &'a mut Vec<PathBuf>,
+    include_stack: Vec<PathBuf>,
+    failed: bool,
+}
+
+impl ConfigLoader<'_> {
+    /// Loads the file and merges it on top of its includes.
+    fn load_with_includes(&mut self, path: &Path) -> Option<Config> {
+        if self.include_stack.iter().any(|p| p == path) {
+            let message = format!("Circular include of `{}`", path.display());
+            self.error(Diagnostic::error(message));
+            return None;
+        }
+
+        let mut file_config = self.load_file(path)?;
+
+        self.include_stack.push(path.to_path_buf());
+
+        let mut config = Config::default();
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        for include in std::mem::take(&mut file_config.include) {
+            if let Some(included) = self.load_with_includes(&base_dir.join(include)) {
+                config.merge(included);
+            }
+        }
+
+        self.include_stack.pop();
+
+        config.merge(file_config);
+        Some(config)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Option<Config> {
+        if !self.files.iter().any(|p| p == path) {
+            self.files.push(path.to_path_buf());
+        }
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                let message = format!("Failed to open config: {}", error);
+                self.error(Diagnostic::error(message).in_file(path));
+                return None;
+            }
+        };
+
+        let deserializer = match toml::de::Deserializer::parse(&contents) {
+            Ok(deserializer) => deserializer,
+            Err(error) => {
+                self.error(toml_error_diagnostic(path, &contents, &error));
+                return None;
+            }
+        };
+
+        let mut unknown_keys = Vec::new();
+        let config = serde_ignored::deserialize(deserializer, |key| {
+            unknown_keys.push(format_key_path(&key));
+        });
+
+        match config {
+            Ok(mut config) => {
+                for key in unknown_keys {
+                    let message = format!("Unknown key `{}`", key);
+                    self.diagnostics
+                        .push(Diagnostic::warning(message).in_file(path));
+                }
+                self.expand_paths(path, &mut config);
+                Some(config)
+            }
+            Err(error) => {
+                self.error(toml_error_diagnostic(path, &contents, &error));
+                None
+            }
+        }
+    }
+
+    /// Expands `~` and variables in path values, failed values are kept as is.
+    fn expand_paths(&mut self, path: &Path, config: &mut Config) {
+        let launch_env = config.launch.iter_mut().flat_map(|l| l.env.values_mut());
+        let values = config
+            .include
+            .iter_mut()
+            .chain(launch_env)
+            .chain(item_paths(&mut config.items));
+
+        for value in values {
+            match expand_path(value) {
+                Ok(expanded) => *value = expanded,
+                Err(error) => {
+                    let message = format!("Failed to expand `{}`: {}", value, error);
+                    self.diagnostics
+                        .push(Diagnostic::error(message).in_file(path));
+                }
+            }
+        }
+    }
+
+    fn error(&mut self, diagnostic: Diagnostic) {
+        self.failed = true;
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// Path values of the items, including items inside folders.
+fn item_paths(items: &mut [AppIconConfig]) -> Vec<&mut String> {
+    items
+        .iter_mut()
+        .flat_map(|item| {
+            let stdio = [&mut item.stdin, &mut item.stdout, &mut item.stderr]
+                .into_iter()
+                .filter_map(|stdio| match stdio {
+                    Some(StdioConfig::File(path)) => Some(path),
+                    _ => None,
+                });
+
+            [&mut item.icon]
+                .into_iter()
+                .chain(item.cwd.as_mut())
+                .chain(item.env.values_mut())
+                .chain(stdio)
+                .chain(item_paths(&mut item.items))
+        })
+        .collect()
+}
+
+fn format_key_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", format_key_path(parent), index),
+        Path::Map { parent, key } => match format_key_path(parent) {
+            parent if parent.is_empty() => key.clone(),
+            parent => format!("{}.{}", parent, key),
+        },
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => format_key_path(parent),
+    }
+}
+
+fn toml_error_diagnostic(path: &Path, contents: &str, error: &toml::de::Error) -> Diagnostic {
+    let diagnostic = Diagnostic::error(error.message().trim()).in_file(path);
+    match error.span() {
+        Some(span) => diagnostic.at(contents, span.start),
+        None => diagnostic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_paths_of_items_in_folders() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[items]]
+            name = "Games"
+            icon = "~/icons/games.png"
+            type = "folder"
+
+            [[items.items]]
+            name = "Doom"
+            icon = "~/icons/doom.png"
+            cwd = "$HOME/doom"
+            stdout = { file = "~/doom.log" }
+            env = { SAVES = "${HOME}/saves" }
+            "#,
+        )
+        .unwrap();
+
+        let mut diagnostics = Diagnostics::default();
+        let mut files = Vec::new();
+        let mut loader = ConfigLoader {
+            diagnostics: &mut diagnostics,
+            files: &mut files,
+            include_stack: Vec::new(),
+            failed: false,
+        };
+        loader.expand_paths(Path::new("gpcl.toml"), &mut config);
+        assert_eq!(diagnostics.len(), 0);
+
+        let home = std::env::var("HOME").unwrap();
+        let folder = &config.items[0];
+        assert_eq!(folder.icon, format!("{}/icons/games.png", home));
+
+        let item = &folder.items[0];
+        assert_eq!(item.icon, format!("{}/icons/doom.png", home));
+        assert_eq!(item.cwd, Some(format!("{}/doom", home)));
+        assert_eq!(item.env["SAVES"], format!("{}/saves", home));
+        assert!(
+            matches!(&item.stdout, Some(StdioConfig::File(path)) if *path == format!("{}/doom.log", home))
+        );
+    }
+
+    #[test]
+    fn merges_includes_before_file_and_drop_ins_after_it() {
+        let dir = std::env::temp_dir().join(format!("gpcl-config-{}", std::process::id()));
+        let drop_ins = dir.join("gpcl.d");
+        fs::create_dir_all(&drop_ins).unwrap();
+
+        let item = |name: &str| format!("[[items]]\nname = \"{}\"\nicon = \"{}\"\n", name, name);
+        let main = format!(
+            "include = [\"base.toml\"]\nlayout = {{ mode = \"grid\" }}\n{}",
+            item("Main")
+        );
+        fs::write(dir.join("gpcl.toml"), main).unwrap();
+        let base = format!(
+            "layout = {{ mode = \"list\", icon_size = 64.0 }}\n{}",
+            item("Base")
+        );
+        fs::write(dir.join("base.toml"), base).unwrap();
+        fs::write(drop_ins.join("20-b.toml"), item("B")).unwrap();
+        let drop_in = format!("layout = {{ icon_size = 32.0 }}\n{}", item("A"));
+        fs::write(drop_ins.join("10-a.toml"), drop_in).unwrap();
+        fs::write(drop_ins.join("30-c.txt"), item("Ignored")).unwrap();
+
+        let mut diagnostics = Diagnostics::default();
+        let mut files = Vec::new();
+        let config = load_config(&dir.join("gpcl.toml"), &mut diagnostics, &mut files);
+        _ = fs::remove_dir_all(&dir);
+
+        let config = config.unwrap();
+        let names: Vec<_> = config.items.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, ["Base", "Main", "A", "B"]);
+        let layout = config.layout.unwrap();
+        assert_eq!(layout.mode, Some(crate::config::LayoutMode::Grid));
+        assert_eq!(layout.icon_size, Some(32.0));
+        assert_eq!(files.len(), 4);
+    }
+}
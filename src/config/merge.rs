@@ -0,0 +1,191 @@
+use crate::config::{
+    Config, ForceQuitConfig, KioskConfig, LaunchConfig, LayoutConfig, StyleConfig,
+};
+
+impl Config {
+    /// Merges the other config on top of this one:
+    /// layout, style, launch, force quit and kiosk values are overridden per key,
+    /// items, removed items and launch hooks are appended, wrappers are replaced by name,
+    /// the desktop entries section and the item order are replaced.
+    pub fn merge(&mut self, other: Config) {
+        merge_section(&mut self.layout, other.layout, LayoutConfig::merge);
+        merge_section(&mut self.style, other.style, StyleConfig::merge);
+        merge_section(&mut self.launch, other.launch, LaunchConfig::merge);
+        merge_section(
+            &mut self.force_quit,
+            other.force_quit,
+            ForceQuitConfig::merge,
+        );
+        merge_section(&mut self.kiosk, other.kiosk, KioskConfig::merge);
+        self.wrappers.extend(other.wrappers);
+
+        if other.desktop_entries.is_some() {
+            self.desktop_entries = other.desktop_entries;
+        }
+
+        if other.item_order.is_some() {
+            self.item_order = other.item_order;
+        }
+
+        self.remove_items.extend(other.remove_items);
+        self.items.extend(other.items);
+    }
+}
+
+impl LayoutConfig {
+    fn merge(&mut self, other: LayoutConfig) {
+        merge_value(&mut self.mode, other.mode);
+        merge_value(&mut self.sort, other.sort);
+        merge_value(&mut self.recent_items, other.recent_items);
+        merge_value(&mut self.top_panel_height, other.top_panel_height);
+        merge_value(&mut self.clock_height, other.clock_height);
+        merge_value(&mut self.icon_size, other.icon_size);
+    }
+}
+
+impl StyleConfig {
+    fn merge(&mut self, other: StyleConfig) {
+        merge_value(&mut self.font, other.font);
+        merge_value(&mut self.font_weight, other.font_weight);
+        merge_value(&mut self.bg_color, other.bg_color);
+        merge_value(&mut self.panel_color, other.panel_color);
+        merge_value(&mut self.text_color, other.text_color);
+        merge_value(&mut self.icon_theme, other.icon_theme);
+    }
+}
+
+impl LaunchConfig {
+    fn merge(&mut self, other: LaunchConfig) {
+        self.env.extend(other.env);
+        self.pre_exec.extend(other.pre_exec);
+        self.post_exec.extend(other.post_exec);
+        merge_value(&mut self.hook_timeout, other.hook_timeout);
+        merge_value(&mut self.wrappers, other.wrappers);
+    }
+}
+
+impl ForceQuitConfig {
+    fn merge(&mut self, other: ForceQuitConfig) {
+        merge_value(&mut self.buttons, other.buttons);
+        merge_value(&mut self.hold_time, other.hold_time);
+        merge_value(&mut self.kill_timeout, other.kill_timeout);
+    }
+}
+
+impl KioskConfig {
+    fn merge(&mut self, other: KioskConfig) {
+        merge_value(&mut self.autostart, other.autostart);
+        merge_value(&mut self.restart, other.restart);
+        merge_value(&mut self.restart_delay, other.restart_delay);
+        merge_value(&mut self.max_restarts, other.max_restarts);
+    }
+}
+
+fn merge_section<T>(base: &mut Option<T>, other: Option<T>, merge: fn(&mut T, T)) {
+    match (base.as_mut(), other) {
+        (Some(base), Some(other)) => merge(base, other),
+        (None, Some(other)) => *base = Some(other),
+        (_, None) => {}
+    }
+}
+
+fn merge_value<T>(base: &mut Option<T>, other: Option<T>) {
+    if other.is_some() {
+        *base = other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExecConfig, LayoutMode};
+
+    fn parse(config: &str) -> Config {
+        toml::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn overrides_sections_per_key() {
+        let mut config = parse(
+            r#"
+            layout = { mode = "grid", icon_size = 128.0 }
+            style = { font = "Sans" }
+            kiosk = { autostart = "Kodi", max_restarts = 3 }
+            "#,
+        );
+        config.merge(parse(
+            r#"
+            layout = { icon_size = 96.0 }
+            style = { font = "Serif", icon_theme = "Papirus" }
+            "#,
+        ));
+
+        let layout = config.layout.unwrap();
+        assert_eq!(layout.mode, Some(LayoutMode::Grid));
+        assert_eq!(layout.icon_size, Some(96.0));
+        let style = config.style.unwrap();
+        assert_eq!(style.font.as_deref(), Some("Serif"));
+        assert_eq!(style.icon_theme.as_deref(), Some("Papirus"));
+        let kiosk = config.kiosk.unwrap();
+        assert_eq!(kiosk.autostart.as_deref(), Some("Kodi"));
+        assert_eq!(kiosk.max_restarts, Some(3));
+    }
+
+    #[test]
+    fn appends_items_and_hooks() {
+        let mut config = parse(
+            r#"
+            remove_items = ["Shutdown"]
+            launch = { env = { A = "1", B = "1" }, pre_exec = ["true"], wrappers = ["gamemode"] }
+            items = [{ name = "Kodi", icon = "kodi" }]
+            "#,
+        );
+        config.merge(parse(
+            r#"
+            remove_items = ["Reboot"]
+            launch = { env = { B = "2" }, pre_exec = ["false"] }
+            items = [{ name = "Steam", icon = "steam" }]
+            "#,
+        ));
+
+        assert_eq!(config.remove_items, ["Shutdown", "Reboot"]);
+        let names: Vec<_> = config.items.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, ["Kodi", "Steam"]);
+
+        let launch = config.launch.unwrap();
+        assert_eq!(launch.env["A"], "1");
+        assert_eq!(launch.env["B"], "2");
+        assert_eq!(launch.pre_exec.len(), 2);
+        assert_eq!(launch.wrappers, Some(vec!["gamemode".to_string()]));
+    }
+
+    #[test]
+    fn replaces_order_wrappers_and_desktop_entries() {
+        let mut config = parse(
+            r#"
+            item_order = ["Kodi"]
+            desktop_entries = { ids = ["firefox"] }
+            wrappers.gamemode = { prefix = "gamemoderun" }
+            wrappers.mangohud = { prefix = "mangohud" }
+            "#,
+        );
+        config.merge(parse(
+            r#"
+            desktop_entries = {}
+            wrappers.gamemode = { prefix = ["gamemoderun", "-v"] }
+            "#,
+        ));
+
+        // The order is kept as it isn't defined by the other config
+        assert_eq!(config.item_order, Some(vec!["Kodi".to_string()]));
+        assert!(config.desktop_entries.as_ref().unwrap().ids.is_none());
+        assert_eq!(config.wrappers.len(), 2);
+        assert!(matches!(
+            &config.wrappers["gamemode"].prefix,
+            ExecConfig::Args(args) if args.len() == 2
+        ));
+
+        config.merge(parse(r#"item_order = ["Steam"]"#));
+        assert_eq!(config.item_order, Some(vec!["Steam".to_string()]));
+    }
+}
@@ -0,0 +1,336 @@
+mod diagnostics;
+mod loader;
+mod merge;
+
+pub use diagnostics::{Diagnostic, Diagnostics, Severity};
+pub use loader::{drop_in_dir, load_config};
+
+use crate::desktop_entries::load_desktop_entries;
+
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Default, Deserialize)]
+pub struct Config {
+    pub layout: Option<LayoutConfig>,
+    pub style: Option<StyleConfig>,
+    pub desktop_entries: Option<DesktopEntriesConfig>,
+    pub launch: Option<LaunchConfig>,
+    pub force_quit: Option<ForceQuitConfig>,
+    pub kiosk: Option<KioskConfig>,
+
+    #[serde(default)]
+    pub wrappers: BTreeMap<String, WrapperConfig>,
+
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    #[serde(default)]
+    pub remove_items: Vec<String>,
+    pub item_order: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub items: Vec<AppIconConfig>,
+}
+
+impl Config {
+    /// Takes manually defined items followed by items imported from desktop entries.
+    /// Removed items are filtered out and ordered items are moved to the beginning of the list,
+    /// both also apply to items inside folders.
+    pub fn take_items(&mut self) -> Vec<AppIconConfig> {
+        let mut items = std::mem::take(&mut self.items);
+        if let Some(desktop_entries) = &self.desktop_entries {
+            items.extend(load_desktop_entries(desktop_entries));
+        }
+
+        remove_items(&mut items, &self.remove_items);
+
+        if let Some(order) = &self.item_order {
+            order_items(&mut items, order);
+        }
+
+        items
+    }
+}
+
+/// Removes items by name, including items inside folders.
+fn remove_items(items: &mut Vec<AppIconConfig>, names: &[String]) {
+    items.retain(|item| !names.contains(&item.name));
+    for item in items {
+        remove_items(&mut item.items, names);
+    }
+}
+
+/// Moves the listed items to the beginning of their list, including items inside folders.
+fn order_items(items: &mut [AppIconConfig], order: &[String]) {
+    // Stable sort keeps the original order of items that are not listed
+    items.sort_by_key(|item| {
+        order
+            .iter()
+            .position(|name| *name == item.name)
+            .unwrap_or(order.len())
+    });
+    for item in items {
+        order_items(&mut item.items, order);
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub struct LayoutConfig {
+    pub mode: Option<LayoutMode>,
+    pub sort: Option<SortOrder>,
+    /// Number of items in the "Recent" folder, it's not shown if not set
+    pub recent_items: Option<usize>,
+    pub top_panel_height: Option<f32>,
+    pub clock_height: Option<f32>,
+    pub icon_size: Option<f32>,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutMode {
+    #[default]
+    Row,
+    Grid,
+    List,
+}
+
+#[derive(Default, Deserialize)]
+pub struct StyleConfig {
+    pub font: Option<String>,
+    pub font_weight: Option<i32>,
+    pub bg_color: Option<String>,
+    pub panel_color: Option<String>,
+    pub text_color: Option<String>,
+    pub icon_theme: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct DesktopEntriesConfig {
+    pub ids: Option<Vec<String>>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct LaunchConfig {
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub pre_exec: Vec<ExecConfig>,
+    #[serde(default)]
+    pub post_exec: Vec<ExecConfig>,
+    pub hook_timeout: Option<f32>,
+
+    /// Wrappers of every item
+    pub wrappers: Option<Vec<String>>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct WrapperConfig {
+    pub prefix: ExecConfig,
+}
+
+#[derive(Default, Deserialize)]
+pub struct ForceQuitConfig {
+    pub buttons: Option<Vec<String>>,
+    pub hold_time: Option<f32>,
+    pub kill_timeout: Option<f32>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct KioskConfig {
+    pub autostart: Option<String>,
+    pub restart: Option<RestartPolicy>,
+    pub restart_delay: Option<f32>,
+    pub max_restarts: Option<u32>,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+#[derive(Default, Deserialize)]
+pub struct AppIconConfig {
+    pub name: String,
+    pub icon: String,
+    #[serde(default, rename = "type")]
+    pub kind: ItemKind,
+    /// Items of the folder
+    #[serde(default)]
+    pub items: Vec<AppIconConfig>,
+
+    #[serde(default)]
+    pub exec: ExecConfig,
+    /// Built-in action that is performed instead of running the exec
+    pub action: Option<ItemAction>,
+
+    #[serde(default)]
+    pub shell: bool,
+    #[serde(default)]
+    pub confirm: bool,
+    pub confirm_message: Option<String>,
+    /// Don't wait for processes left by the item after it exits
+    #[serde(default)]
+    pub detach: bool,
+
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub env_remove: Vec<String>,
+    pub cwd: Option<String>,
+
+    pub stdin: Option<StdioConfig>,
+    pub stdout: Option<StdioConfig>,
+    pub stderr: Option<StdioConfig>,
+
+    #[serde(default)]
+    pub pre_exec: Vec<ExecConfig>,
+    #[serde(default)]
+    pub post_exec: Vec<ExecConfig>,
+
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+    /// Set to `false` to not use wrappers from the launch settings
+    pub default_wrappers: Option<bool>,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    #[default]
+    Config,
+    Recent,
+    MostUsed,
+    Alphabetical,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    #[default]
+    App,
+    Folder,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemAction {
+    #[serde(rename = "poweroff")]
+    PowerOff,
+    Reboot,
+    Suspend,
+    Hibernate,
+    Reload,
+    Quit,
+    Settings,
+    Stats,
+    #[serde(rename = "restart-gpcl")]
+    RestartGpcl,
+}
+
+/// Spelled like in the config.
+impl fmt::Display for ItemAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ItemAction::PowerOff => "poweroff",
+            ItemAction::Reboot => "reboot",
+            ItemAction::Suspend => "suspend",
+            ItemAction::Hibernate => "hibernate",
+            ItemAction::Reload => "reload",
+            ItemAction::Quit => "quit",
+            ItemAction::Settings => "settings",
+            ItemAction::Stats => "stats",
+            ItemAction::RestartGpcl => "restart-gpcl",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ExecConfig {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl Default for ExecConfig {
+    fn default() -> Self {
+        Self::Line(String::new())
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StdioConfig {
+    Null,
+    Inherit,
+    File(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[AppIconConfig]) -> Vec<&str> {
+        items.iter().map(|item| item.name.as_str()).collect()
+    }
+
+    #[test]
+    fn removes_and_orders_items_in_folders() {
+        let mut config: Config = toml::from_str(
+            r#"
+            remove_items = ["Quake"]
+            item_order = ["Games", "Tetris", "Doom"]
+
+            [[items]]
+            name = "Kodi"
+            icon = "kodi"
+
+            [[items]]
+            name = "Games"
+            icon = "folder"
+            type = "folder"
+            items = [
+                { name = "Quake", icon = "quake" },
+                { name = "Pong", icon = "pong" },
+                { name = "Doom", icon = "doom" },
+                { name = "Tetris", icon = "tetris" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let items = config.take_items();
+        assert_eq!(names(&items), ["Games", "Kodi"]);
+        assert_eq!(names(&items[0].items), ["Tetris", "Doom", "Pong"]);
+    }
+
+    #[test]
+    fn displays_actions_like_config() {
+        let actions = [
+            ItemAction::PowerOff,
+            ItemAction::Reboot,
+            ItemAction::Suspend,
+            ItemAction::Hibernate,
+            ItemAction::Reload,
+            ItemAction::Quit,
+            ItemAction::Settings,
+            ItemAction::Stats,
+            ItemAction::RestartGpcl,
+        ];
+        for action in actions {
+            let item: AppIconConfig = toml::from_str(&format!(
+                "name = \"A\"\nicon = \"a\"\naction = \"{}\"",
+                action
+            ))
+            .unwrap();
+            assert_eq!(item.action, Some(action));
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use inotify::{Inotify, WatchDescriptor, WatchMask};
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+const CONFIG_FILE_EXT: &str = "toml";
+
+#[derive(Default)]
+struct WatchedDir {
+    names: Vec<OsString>,
+    config_files: bool,
+}
+
+impl WatchedDir {
+    fn matches(&self, name: &OsStr) -> bool {
+        self.names.iter().any(|n| n == name)
+            || (self.config_files && Path::new(name).extension() == Some(CONFIG_FILE_EXT.as_ref()))
+    }
+}
+
+/// Tracks changes of config files on disk.
+///
+/// Parent directories are watched instead of the files themselves, so files replaced by
+/// an atomic rename (as most editors do on save) keep being tracked.
+pub struct ConfigWatcher {
+    inotify: Inotify,
+    watches: HashMap<WatchDescriptor, WatchedDir>,
+    changed_at: Option<Instant>,
+    buffer: Vec<u8>,
+}
+
+impl ConfigWatcher {
+    pub fn new() -> Result<Self, String> {
+        let inotify = Inotify::init()
+            .map_err(|error| format!("Failed to init config file watcher: {}", error))?;
+
+        Ok(Self {
+            inotify,
+            watches: HashMap::new(),
+            changed_at: None,
+            buffer: vec![0; 4096],
+        })
+    }
+
+    /// Watches the files and all config files in the directories.
+    pub fn watch(&mut self, files: &[PathBuf], dirs: &[PathBuf]) {
+        for (wd, _) in self.watches.drain() {
+            _ = self.inotify.watches().remove(wd);
+        }
+
+        let mask = WatchMask::CLOSE_WRITE
+            | WatchMask::MOVED_TO
+            | WatchMask::MOVED_FROM
+            | WatchMask::CREATE
+            | WatchMask::DELETE;
+
+        // Directories are also watched by name to track their creation and removal
+        for path in files.iter().chain(dirs) {
+            let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+                continue;
+            };
+
+            if let Some(watched) = self.add_watch(dir, mask) {
+                watched.names.push(name.into());
+            }
+        }
+
+        for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+            if let Some(watched) = self.add_watch(dir, mask) {
+                watched.config_files = true;
+            }
+        }
+    }
+
+    fn add_watch(&mut self, dir: &Path, mask: WatchMask) -> Option<&mut WatchedDir> {
+        match self.inotify.watches().add(dir, mask) {
+            Ok(wd) => Some(self.watches.entry(wd).or_default()),
+            Err(error) => {
+                log::warn!("Failed to watch `{}`: {}", dir.display(), error);
+                None
+            }
+        }
+    }
+
+    /// Returns `true` once the watched files stop changing for the debounce delay.
+    pub fn poll(&mut self) -> bool {
+        loop {
+            let events = match self.inotify.read_events(&mut self.buffer) {
+                Ok(events) => events,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::error!("Failed to read config file events: {}", error);
+                    break;
+                }
+            };
+
+            for event in events {
+                let is_watched = match (self.watches.get(&event.wd), event.name) {
+                    (Some(watched), Some(name)) => watched.matches(name),
+                    _ => false,
+                };
+
+                if is_watched {
+                    self.changed_at = Some(Instant::now());
+                }
+            }
+        }
+
+        match self.changed_at {
+            Some(changed_at) if changed_at.elapsed() >= DEBOUNCE_DELAY => {
+                self.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
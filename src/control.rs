@@ -0,0 +1,385 @@
+use crate::state::Selection;
+
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SOCKET_FILE_NAME: &str = "control.sock";
+/// Longer requests are not expected, the client is disconnected to keep the memory bounded
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+/// Clients that don't read replies are disconnected once that much is queued for them
+const MAX_OUTPUT_SIZE: usize = 1024 * 1024;
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Command sent to the control socket as a JSON object on a single line.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    /// Runs the item with the given name like it is activated in the list
+    Launch {
+        item: String,
+    },
+    Reload,
+    State,
+    /// Force quits the running item
+    Kill,
+}
+
+/// Reply to a request, a JSON object on a single line.
+#[derive(Debug, Default, Serialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<LauncherState>,
+}
+
+impl Response {
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(error.into()),
+            state: None,
+        }
+    }
+
+    pub fn state(state: LauncherState) -> Self {
+        Self {
+            ok: true,
+            error: None,
+            state: Some(state),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LauncherState {
+    pub items: Vec<ItemInfo>,
+    pub selection: Selection,
+    /// Name of the running item
+    pub running: Option<String>,
+    pub gamepads: Vec<GamepadInfo>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ItemInfo {
+    pub name: String,
+    pub enabled: bool,
+    /// Items of a folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<ItemInfo>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GamepadInfo {
+    pub name: String,
+    pub status: String,
+    pub charge: i32,
+}
+
+fn socket_path() -> io::Result<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix(env!("CARGO_PKG_NAME"));
+    xdg_dirs.get_runtime_file(SOCKET_FILE_NAME)
+}
+
+struct Client {
+    stream: UnixStream,
+    buffer: Vec<u8>,
+    /// Replies that are not written yet, they are sent as the socket accepts them
+    output: Vec<u8>,
+    /// No more requests are read, the client is disconnected once the output is written
+    closing: bool,
+}
+
+impl Client {
+    fn new(stream: UnixStream) -> Self {
+        Self {
+            stream,
+            buffer: Vec::new(),
+            output: Vec::new(),
+            closing: false,
+        }
+    }
+}
+
+/// Listens on `$XDG_RUNTIME_DIR/gpcl/control.sock`.
+/// It never blocks, so it's polled from the event loop.
+pub struct ControlServer {
+    path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<Client>,
+}
+
+impl ControlServer {
+    pub fn bind() -> Result<Self, String> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix(env!("CARGO_PKG_NAME"));
+        let path = xdg_dirs
+            .place_runtime_file(SOCKET_FILE_NAME)
+            .map_err(|error| format!("Failed to create control socket directory: {}", error))?;
+
+        // The socket is left behind if gpcl is killed
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(format!(
+                    "Control socket {} is used by another instance",
+                    path.display()
+                ));
+            }
+            _ = fs::remove_file(&path);
+        }
+
+        let listener = UnixListener::bind(&path)
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|error| format!("Failed to listen on {}: {}", path.display(), error))?;
+
+        log::info!("Listen for commands on {}", path.display());
+        Ok(Self {
+            path,
+            listener,
+            clients: Vec::new(),
+        })
+    }
+
+    /// Accepts connections and passes received requests to the handler.
+    pub fn poll(&mut self, mut handle: impl FnMut(Request) -> Response) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => match stream.set_nonblocking(true) {
+                    Ok(()) => self.clients.push(Client::new(stream)),
+                    Err(error) => log::error!("Failed to set up control client: {}", error),
+                },
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::error!("Failed to accept control client: {}", error);
+                    break;
+                }
+            }
+        }
+
+        self.clients
+            .retain_mut(|client| poll_client(client, &mut handle));
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Handles complete lines received from the client and writes queued replies,
+/// returns false when it's disconnected.
+fn poll_client(client: &mut Client, handle: &mut impl FnMut(Request) -> Response) -> bool {
+    let mut connected = true;
+    let mut chunk = [0; 4096];
+    while !client.closing {
+        match client.stream.read(&mut chunk) {
+            Ok(0) => {
+                connected = false;
+                break;
+            }
+            Ok(size) => {
+                client.buffer.extend_from_slice(&chunk[..size]);
+                handle_lines(client, handle);
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => {
+                log::warn!("Failed to read from control client: {}", error);
+                return false;
+            }
+        }
+    }
+
+    if let Err(error) = write_output(client) {
+        log::warn!("Failed to reply to control client: {}", error);
+        return false;
+    }
+    if client.output.len() > MAX_OUTPUT_SIZE {
+        log::warn!("Control client doesn't read replies, disconnecting it");
+        return false;
+    }
+
+    connected && (!client.closing || !client.output.is_empty())
+}
+
+fn handle_lines(client: &mut Client, handle: &mut impl FnMut(Request) -> Response) {
+    while let Some(end) = client.buffer.iter().position(|&byte| byte == b'\n') {
+        let line: Vec<_> = client.buffer.drain(..=end).collect();
+        let line = String::from_utf8_lossy(&line);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => {
+                log::info!("Control request: {:?}", request);
+                handle(request)
+            }
+            Err(error) => Response::error(format!("Invalid request: {}", error)),
+        };
+        queue_response(client, &response);
+    }
+
+    if client.buffer.len() > MAX_REQUEST_SIZE {
+        log::warn!("Control request is too large, disconnecting the client");
+        client.buffer = Vec::new();
+        client.closing = true;
+        queue_response(client, &Response::error("Request is too large"));
+    }
+}
+
+fn queue_response(client: &mut Client, response: &Response) {
+    match serde_json::to_vec(response) {
+        Ok(line) => {
+            client.output.extend_from_slice(&line);
+            client.output.push(b'\n');
+        }
+        Err(error) => log::error!("Failed to serialize control reply: {}", error),
+    }
+}
+
+/// Writes as much of the queued output as the socket accepts without blocking.
+fn write_output(client: &mut Client) -> io::Result<()> {
+    while !client.output.is_empty() {
+        match client.stream.write(&client.output) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(size) => _ = client.output.drain(..size),
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(())
+}
+
+/// Sends the request to the running launcher and returns the response line.
+pub fn send_request(request: &Request) -> Result<String, String> {
+    let path =
+        socket_path().map_err(|error| format!("Failed to find control socket: {}", error))?;
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|error| format!("Failed to connect to {}: {}", path.display(), error))?;
+
+    let mut line = serde_json::to_string(request).map_err(|error| error.to_string())?;
+    line.push('\n');
+
+    let mut response = String::new();
+    stream
+        .set_read_timeout(Some(CLIENT_TIMEOUT))
+        .and_then(|_| stream.write_all(line.as_bytes()))
+        .and_then(|_| BufReader::new(&stream).read_line(&mut response))
+        .map_err(|error| format!("Failed to send the command: {}", error))?;
+
+    if response.is_empty() {
+        return Err("The launcher closed the connection".to_string());
+    }
+    Ok(response.trim_end().to_string())
+}
+
+/// Whether the response line reports success.
+pub fn is_ok_response(response: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(response)
+        .map(|response| response["ok"] == true)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests() {
+        let launch = serde_json::from_str::<Request>(r#"{"command": "launch", "item": "Kodi"}"#);
+        let expected = Request::Launch {
+            item: "Kodi".to_string(),
+        };
+        assert_eq!(launch.unwrap(), expected);
+
+        let kill = serde_json::from_str::<Request>(r#"{"command": "kill"}"#);
+        assert_eq!(kill.unwrap(), Request::Kill);
+
+        assert!(serde_json::from_str::<Request>(r#"{"command": "launch"}"#).is_err());
+        assert!(serde_json::from_str::<Request>(r#"{"command": "format"}"#).is_err());
+    }
+
+    #[test]
+    fn serializes_responses() {
+        let response = serde_json::to_string(&Response::ok()).unwrap();
+        assert_eq!(response, r#"{"ok":true}"#);
+
+        let response = serde_json::to_string(&Response::error("Item `x` is not found")).unwrap();
+        assert_eq!(response, r#"{"ok":false,"error":"Item `x` is not found"}"#);
+        assert!(!is_ok_response(&response));
+        assert!(is_ok_response(r#"{"ok":true}"#));
+    }
+
+    #[test]
+    fn replies_to_each_line() {
+        let (mut local, remote) = UnixStream::pair().unwrap();
+        remote.set_nonblocking(true).unwrap();
+        let mut client = Client::new(remote);
+
+        local
+            .write_all(b"{\"command\":\"reload\"}\n\n{\"command\":\"kill\"}\n{\"comm")
+            .unwrap();
+
+        let mut requests = Vec::new();
+        let connected = poll_client(&mut client, &mut |request| {
+            requests.push(request);
+            Response::ok()
+        });
+
+        assert!(connected);
+        assert_eq!(requests, [Request::Reload, Request::Kill]);
+        assert_eq!(client.buffer, b"{\"comm");
+
+        let mut replies = String::new();
+        let mut reader = BufReader::new(&local);
+        reader.read_line(&mut replies).unwrap();
+        reader.read_line(&mut replies).unwrap();
+        assert_eq!(replies, "{\"ok\":true}\n{\"ok\":true}\n");
+
+        drop(local);
+        assert!(!poll_client(&mut client, &mut |_| Response::ok()));
+    }
+
+    #[test]
+    fn disconnects_clients_with_too_large_requests() {
+        let (mut local, remote) = UnixStream::pair().unwrap();
+        remote.set_nonblocking(true).unwrap();
+        let mut client = Client::new(remote);
+
+        let request = vec![b' '; MAX_REQUEST_SIZE + 1];
+        local.set_nonblocking(true).unwrap();
+        let mut written = 0;
+        while written < request.len() {
+            match local.write(&request[written..]) {
+                Ok(size) => written += size,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    poll_client(&mut client, &mut |_| Response::ok());
+                }
+                Err(error) => panic!("{}", error),
+            }
+        }
+        local.set_nonblocking(false).unwrap();
+
+        let connected = poll_client(&mut client, &mut |_| Response::ok());
+        assert!(!connected);
+        assert!(client.buffer.is_empty());
+
+        let mut reply = String::new();
+        BufReader::new(&local).read_line(&mut reply).unwrap();
+        assert_eq!(reply, "{\"ok\":false,\"error\":\"Request is too large\"}\n");
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::{AppIconConfig, DesktopEntriesConfig, ExecConfig};
+use crate::key_file::KeyFile;
+use crate::program_path::find_program;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const APPLICATIONS_DIR: &str = "applications";
+const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+const DESKTOP_FILE_EXT: &str = "desktop";
+
+/// Desktop files found in the data directories, the config is reloaded on every change,
+/// so they are only searched again when any of the directories is modified.
+static DESKTOP_FILES: Mutex<Option<DesktopFiles>> = Mutex::new(None);
+
+struct DesktopFiles {
+    /// Scanned directories with their modification times
+    dirs: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Paths of the files by desktop ID
+    files: HashMap<String, PathBuf>,
+}
+
+struct DesktopEntry {
+    entry_type: Option<String>,
+    name: Option<String>,
+    icon: Option<String>,
+    exec: Option<String>,
+    try_exec: Option<String>,
+    no_display: bool,
+    hidden: bool,
+}
+
+pub fn load_desktop_entries(config: &DesktopEntriesConfig) -> Vec<AppIconConfig> {
+    let mut cache = DESKTOP_FILES
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    let desktop_files = match cache.take() {
+        Some(desktop_files) if desktop_files.is_up_to_date() => desktop_files,
+        _ => find_desktop_files(),
+    };
+    let files = &cache.insert(desktop_files).files;
+
+    match &config.ids {
+        Some(ids) => ids
+            .iter()
+            .filter_map(|id| {
+                let id = normalize_id(id);
+                let path = files.get(&id).or_else(|| {
+                    log::error!("Desktop entry `{}` is not found", id);
+                    None
+                })?;
+                load_item(path)
+            })
+            .collect(),
+        None => {
+            let mut items: Vec<AppIconConfig> = files.values().filter_map(load_item).collect();
+            items.sort_by_cached_key(|item| item.name.to_lowercase());
+            items
+        }
+    }
+}
+
+fn normalize_id(id: &str) -> String {
+    if id.ends_with(".desktop") {
+        id.to_string()
+    } else {
+        format!("{}.{}", id, DESKTOP_FILE_EXT)
+    }
+}
+
+impl DesktopFiles {
+    /// Added and removed files change the modification time of their directory.
+    fn is_up_to_date(&self) -> bool {
+        self.dirs
+            .iter()
+            .all(|(dir, modified)| modification_time(dir) == *modified)
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn find_desktop_files() -> DesktopFiles {
+    let xdg_dirs = xdg::BaseDirectories::new();
+    let mut desktop_files = DesktopFiles {
+        dirs: Vec::new(),
+        files: HashMap::new(),
+    };
+
+    // Directories are listed by priority, so the first found file wins for a desktop ID
+    let data_dirs = xdg_dirs
+        .get_data_home()
+        .into_iter()
+        .chain(xdg_dirs.get_data_dirs());
+    for data_dir in data_dirs {
+        let apps_dir = data_dir.join(APPLICATIONS_DIR);
+        collect_desktop_files(&apps_dir, &apps_dir, &mut desktop_files);
+    }
+
+    desktop_files
+}
+
+fn collect_desktop_files(root: &Path, dir: &Path, desktop_files: &mut DesktopFiles) {
+    // Missing directories are remembered too, so they are noticed once created
+    desktop_files
+        .dirs
+        .push((dir.to_path_buf(), modification_time(dir)));
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path.is_dir() {
+            collect_desktop_files(root, &path, desktop_files);
+            continue;
+        }
+
+        if path.extension().is_none_or(|ext| ext != DESKTOP_FILE_EXT) {
+            continue;
+        }
+
+        if let Some(id) = desktop_id(root, &path) {
+            desktop_files.files.entry(id).or_insert(path);
+        }
+    }
+}
+
+fn desktop_id(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<&str> = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    Some(parts.join("-"))
+}
+
+fn load_item(path: &PathBuf) -> Option<AppIconConfig> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| log::error!("Failed to read `{}`: {}", path.display(), error))
+        .ok()?;
+
+    entry_into_item(parse_desktop_entry(&contents), path)
+}
+
+/// Applications that are hidden or not installed are skipped.
+fn entry_into_item(entry: DesktopEntry, path: &Path) -> Option<AppIconConfig> {
+    if entry.entry_type.as_deref() != Some("Application") || entry.no_display || entry.hidden {
+        return None;
+    }
+
+    if let Some(try_exec) = &entry.try_exec {
+        if find_program(try_exec).is_none() {
+            log::debug!("Skip `{}`: `{}` is not available", path.display(), try_exec);
+            return None;
+        }
+    }
+
+    let (Some(name), Some(exec)) = (entry.name, entry.exec) else {
+        log::error!("Desktop entry `{}` has no name or exec", path.display());
+        return None;
+    };
+
+    Some(AppIconConfig {
+        name,
+        icon: entry.icon.unwrap_or_default(),
+        exec: ExecConfig::Line(strip_field_codes(&exec)),
+        ..Default::default()
+    })
+}
+
+fn parse_desktop_entry(contents: &str) -> DesktopEntry {
+    let key_file = KeyFile::parse(contents);
+    let get = |key| key_file.get(DESKTOP_ENTRY_GROUP, key).map(unescape_value);
+
+    DesktopEntry {
+        entry_type: get("Type"),
+        name: get("Name"),
+        icon: get("Icon"),
+        exec: get("Exec"),
+        try_exec: get("TryExec"),
+        no_display: key_file.get_bool(DESKTOP_ENTRY_GROUP, "NoDisplay"),
+        hidden: key_file.get_bool(DESKTOP_ENTRY_GROUP, "Hidden"),
+    }
+}
+
+fn unescape_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(other) => {
+                // Keep other escapes as is, they are handled by the exec line parser
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+fn strip_field_codes(exec: &str) -> String {
+    let mut result = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        // The launcher never passes files or URLs, so all field codes expand to nothing
+        if let Some('%') = chars.next() {
+            result.push('%');
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(contents: &str) -> Option<AppIconConfig> {
+        entry_into_item(parse_desktop_entry(contents), Path::new("test.desktop"))
+    }
+
+    fn exec_line(item: &AppIconConfig) -> &str {
+        match &item.exec {
+            ExecConfig::Line(line) => line,
+            ExecConfig::Args(_) => panic!("Exec is not a line"),
+        }
+    }
+
+    #[test]
+    fn parses_desktop_entry() {
+        let item = item(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Steam\\sBig Picture\n\
+             Name[de]=Steam\n\
+             Icon=steam\n\
+             Exec=steam -gamepadui %U\n\
+             [Desktop Action Library]\n\
+             Exec=steam steam://open/games\n",
+        )
+        .unwrap();
+
+        assert_eq!(item.name, "Steam Big Picture");
+        assert_eq!(item.icon, "steam");
+        assert_eq!(exec_line(&item), "steam -gamepadui ");
+    }
+
+    #[test]
+    fn unescapes_values() {
+        assert_eq!(unescape_value(r"a\sb\tc\nd\re"), "a b\tc\nd\re");
+        // Quoting escapes are left to the exec line parser
+        assert_eq!(unescape_value(r#"echo \"a\\b\""#), r#"echo \"a\\b\""#);
+        assert_eq!(unescape_value(r"trailing\"), r"trailing\");
+    }
+
+    #[test]
+    fn strips_field_codes() {
+        assert_eq!(strip_field_codes("vlc %U"), "vlc ");
+        assert_eq!(strip_field_codes("app %f --name %c %k"), "app  --name  ");
+        assert_eq!(strip_field_codes("printf 100%%"), "printf 100%");
+        assert_eq!(strip_field_codes("app %"), "app ");
+    }
+
+    #[test]
+    fn skips_hidden_and_unavailable_entries() {
+        let entry = |extra: &str| {
+            format!(
+                "[Desktop Entry]\nType=Application\nName=App\nExec=app\n{}",
+                extra
+            )
+        };
+
+        assert!(item(&entry("")).is_some());
+        assert!(item(&entry("NoDisplay=true")).is_none());
+        assert!(item(&entry("Hidden=true")).is_none());
+        assert!(item(&entry("NoDisplay=false")).is_some());
+        assert!(item(&entry("TryExec=sh")).is_some());
+        assert!(item(&entry("TryExec=gpcl-missing-program")).is_none());
+
+        let link = "[Desktop Entry]\nType=Link\nName=Site\nURL=https://example.com\n";
+        assert!(item(link).is_none());
+        assert!(item("[Desktop Entry]\nType=Application\nName=App\n").is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use std::env;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, PartialEq)]
+pub enum ExpandError {
+    UndefinedVariable(String),
+    UnterminatedBrace,
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndefinedVariable(name) => write!(f, "undefined variable `{}`", name),
+            Self::UnterminatedBrace => write!(f, "unterminated `${{`"),
+        }
+    }
+}
+
+/// Expands the leading `~` and `$VAR`, `${VAR}`, `${VAR:-default}` references in the path.
+pub fn expand_path(value: &str) -> Result<String, ExpandError> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    if chars.peek() == Some(&'~') {
+        chars.next();
+        if is_tilde_end(chars.peek()) {
+            result.push_str(&home_dir()?);
+        } else {
+            result.push('~');
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => expand_variable(&mut chars, &mut result)?,
+            c => result.push(c),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns `true` if `~` followed by the character is a home directory reference.
+pub fn is_tilde_end(next: Option<&char>) -> bool {
+    match next {
+        None | Some('/') => true,
+        Some(c) => c.is_whitespace(),
+    }
+}
+
+pub fn home_dir() -> Result<String, ExpandError> {
+    env::var("HOME").map_err(|_| ExpandError::UndefinedVariable("HOME".into()))
+}
+
+/// Expands the variable reference that follows an already consumed `$` into the output.
+/// A `$` that is not followed by a variable name is kept as is.
+pub fn expand_variable(
+    chars: &mut Peekable<Chars>,
+    output: &mut String,
+) -> Result<(), ExpandError> {
+    if chars.peek() == Some(&'{') {
+        chars.next();
+        let mut reference = String::new();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) => reference.push(c),
+                None => return Err(ExpandError::UnterminatedBrace),
+            }
+        }
+
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference.as_str(), None),
+        };
+
+        // Like in shell, the default value is also used for empty variables
+        let value = match (env::var(name).ok(), default) {
+            (Some(value), Some(default)) if value.is_empty() => default.to_string(),
+            (Some(value), _) => value,
+            (None, Some(default)) => default.to_string(),
+            (None, None) => return Err(ExpandError::UndefinedVariable(name.into())),
+        };
+
+        output.push_str(&value);
+        return Ok(());
+    }
+
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if c != '_' && !c.is_ascii_alphanumeric() {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+
+    if name.is_empty() {
+        output.push('$');
+        return Ok(());
+    }
+
+    let value = env::var(&name).map_err(|_| ExpandError::UndefinedVariable(name))?;
+    output.push_str(&value);
+    Ok(())
+}
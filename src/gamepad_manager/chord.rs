@@ -0,0 +1,118 @@
+use crate::config::{Diagnostic, Diagnostics, ForceQuitConfig};
+use gilrs::{Button, Gilrs};
+use std::time::{Duration, Instant};
+
+const DEFAULT_BUTTONS: &[Button] = &[Button::Mode];
+const DEFAULT_HOLD_TIME: Duration = Duration::from_secs(2);
+
+/// Buttons that have to be held together on a single gamepad for some time.
+#[derive(Clone)]
+pub struct ButtonChord {
+    buttons: Vec<Button>,
+    hold_time: Duration,
+}
+
+impl Default for ButtonChord {
+    fn default() -> Self {
+        Self {
+            buttons: DEFAULT_BUTTONS.to_vec(),
+            hold_time: DEFAULT_HOLD_TIME,
+        }
+    }
+}
+
+impl ButtonChord {
+    /// Unknown buttons are reported as warnings and the default chord is used instead.
+    pub fn from_config(config: &ForceQuitConfig, diagnostics: &mut Diagnostics) -> Self {
+        let mut chord = Self::default();
+
+        if let Some(names) = &config.buttons {
+            let buttons: Result<Vec<_>, _> = names
+                .iter()
+                .map(|name| parse_button(name).ok_or(name))
+                .collect();
+
+            match buttons {
+                Ok(buttons) => chord.buttons = buttons,
+                Err(name) => {
+                    let message = format!("Unknown gamepad button `{}` in force quit", name);
+                    diagnostics.push(Diagnostic::warning(message));
+                }
+            }
+        }
+
+        if let Some(hold_time) = config.hold_time {
+            match Duration::try_from_secs_f32(hold_time) {
+                Ok(hold_time) => chord.hold_time = hold_time,
+                Err(_) => {
+                    let message = format!("Invalid force quit hold time `{}`", hold_time);
+                    diagnostics.push(Diagnostic::warning(message));
+                }
+            }
+        }
+
+        chord
+    }
+}
+
+/// Detects a button chord held on any connected gamepad.
+#[derive(Default)]
+pub struct ChordDetector {
+    held_since: Option<Instant>,
+    triggered: bool,
+}
+
+impl ChordDetector {
+    /// Returns `true` once per hold when the chord is held long enough.
+    pub fn update(&mut self, gilrs: &Gilrs, chord: &ButtonChord) -> bool {
+        let is_held = !chord.buttons.is_empty()
+            && gilrs.gamepads().any(|(_, gamepad)| {
+                chord
+                    .buttons
+                    .iter()
+                    .all(|&button| gamepad.is_pressed(button))
+            });
+
+        if !is_held {
+            self.held_since = None;
+            self.triggered = false;
+            return false;
+        }
+
+        let held_since = *self.held_since.get_or_insert_with(Instant::now);
+        if self.triggered || held_since.elapsed() < chord.hold_time {
+            return false;
+        }
+
+        self.triggered = true;
+        true
+    }
+}
+
+/// Parses a button name in snake case, e.g. `mode` or `left_trigger`.
+fn parse_button(name: &str) -> Option<Button> {
+    let button = match name {
+        "south" => Button::South,
+        "east" => Button::East,
+        "north" => Button::North,
+        "west" => Button::West,
+        "c" => Button::C,
+        "z" => Button::Z,
+        "left_trigger" => Button::LeftTrigger,
+        "left_trigger2" => Button::LeftTrigger2,
+        "right_trigger" => Button::RightTrigger,
+        "right_trigger2" => Button::RightTrigger2,
+        "select" => Button::Select,
+        "start" => Button::Start,
+        "mode" => Button::Mode,
+        "left_thumb" => Button::LeftThumb,
+        "right_thumb" => Button::RightThumb,
+        "dpad_up" => Button::DPadUp,
+        "dpad_down" => Button::DPadDown,
+        "dpad_left" => Button::DPadLeft,
+        "dpad_right" => Button::DPadRight,
+        _ => return None,
+    };
+
+    Some(button)
+}
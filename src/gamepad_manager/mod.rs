@@ -1,10 +1,14 @@
+mod chord;
 mod filter_axis_to_dpad_buttons;
 mod filter_dpad_button_events;
 mod keymap;
 mod model;
 
+pub use chord::ButtonChord;
 use filter_axis_to_dpad_buttons::left_axis_to_dpad_btn;
 use filter_dpad_button_events::filter_wrong_dpad_events;
+
+use chord::ChordDetector;
 use model::{create_model_and_tracking_state, GamepadTrackingState, UpdatePowerInfo};
 
 use gilrs::ev::filter::{axis_dpad_to_button, deadzone, Jitter, Repeat};
@@ -21,6 +25,7 @@ pub struct GamepadManager {
     gilrs: Gilrs,
     states: Vec<GamepadTrackingState>,
     models: Rc<VecModel<GamepadModel>>,
+    force_quit_detector: ChordDetector,
 }
 
 impl GamepadManager {
@@ -47,6 +52,7 @@ impl GamepadManager {
             gilrs,
             states,
             models: Rc::new(models),
+            force_quit_detector: ChordDetector::default(),
         })
     }
 
@@ -54,7 +60,9 @@ impl GamepadManager {
         self.models.clone()
     }
 
-    pub fn poll(&mut self, window: &Window) {
+    /// Dispatches gamepad input to the window while it has focus.
+    /// The force quit chord is tracked regardless of focus, returns `true` when it is held.
+    pub fn poll(&mut self, window: &Window, force_quit_chord: &ButtonChord) -> bool {
         let has_focus = window.has_focus();
 
         let gilrs = &mut self.gilrs;
@@ -112,6 +120,8 @@ impl GamepadManager {
         }
 
         self.update_power_info();
+        self.force_quit_detector
+            .update(&self.gilrs, force_quit_chord)
     }
 
     fn update_power_info(&mut self) {
@@ -0,0 +1,214 @@
+use crate::key_file::KeyFile;
+
+const ICON_THEME_GROUP: &str = "Icon Theme";
+
+#[derive(Clone, Copy, PartialEq)]
+enum DirectoryType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+pub struct ThemeDirectory {
+    pub path: String,
+    size: u32,
+    scale: u32,
+    kind: DirectoryType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDirectory {
+    fn parse(key_file: &KeyFile, path: &str) -> Option<Self> {
+        let size = key_file.get_u32(path, "Size")?;
+
+        let kind = match key_file.get(path, "Type") {
+            Some("Fixed") => DirectoryType::Fixed,
+            Some("Scalable") => DirectoryType::Scalable,
+            _ => DirectoryType::Threshold,
+        };
+
+        Some(Self {
+            path: path.to_string(),
+            size,
+            scale: key_file.get_u32(path, "Scale").unwrap_or(1),
+            kind,
+            min_size: key_file.get_u32(path, "MinSize").unwrap_or(size),
+            max_size: key_file.get_u32(path, "MaxSize").unwrap_or(size),
+            threshold: key_file.get_u32(path, "Threshold").unwrap_or(2),
+        })
+    }
+
+    pub fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+
+        match self.kind {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable => self.min_size <= size && size <= self.max_size,
+            DirectoryType::Threshold => self.size.abs_diff(size) <= self.threshold,
+        }
+    }
+
+    pub fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let target = size * scale;
+
+        match self.kind {
+            DirectoryType::Fixed => (self.size * self.scale).abs_diff(target),
+            DirectoryType::Scalable => distance_to_range(
+                target,
+                self.min_size * self.scale,
+                self.max_size * self.scale,
+            ),
+            DirectoryType::Threshold => distance_to_range(
+                target,
+                self.size.saturating_sub(self.threshold) * self.scale,
+                (self.size + self.threshold) * self.scale,
+            ),
+        }
+    }
+}
+
+fn distance_to_range(value: u32, min: u32, max: u32) -> u32 {
+    if value < min {
+        min - value
+    } else {
+        value.saturating_sub(max)
+    }
+}
+
+pub struct ThemeIndex {
+    pub inherits: Vec<String>,
+    pub directories: Vec<ThemeDirectory>,
+}
+
+impl ThemeIndex {
+    pub fn parse(contents: &str) -> Self {
+        let key_file = KeyFile::parse(contents);
+
+        let inherits = key_file
+            .get_list(ICON_THEME_GROUP, "Inherits")
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        let directories = key_file
+            .get_list(ICON_THEME_GROUP, "Directories")
+            .into_iter()
+            .chain(key_file.get_list(ICON_THEME_GROUP, "ScaledDirectories"))
+            .filter_map(|path| ThemeDirectory::parse(&key_file, path))
+            .collect();
+
+        Self {
+            inherits,
+            directories,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = "\
+[Icon Theme]
+Name=Test
+Inherits=Adwaita,hicolor
+Directories=48x48/apps,scalable/apps,32x32/apps,broken
+ScaledDirectories=48x48@2/apps
+
+[48x48/apps]
+Size=48
+Type=Fixed
+
+[48x48@2/apps]
+Size=48
+Scale=2
+Type=Fixed
+
+[scalable/apps]
+Size=64
+MinSize=16
+MaxSize=256
+Type=Scalable
+
+[32x32/apps]
+Size=32
+
+[broken]
+Type=Fixed
+";
+
+    fn directory<'a>(index: &'a ThemeIndex, path: &str) -> &'a ThemeDirectory {
+        index
+            .directories
+            .iter()
+            .find(|dir| dir.path == path)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_index() {
+        let index = ThemeIndex::parse(INDEX);
+        assert_eq!(index.inherits, ["Adwaita", "hicolor"]);
+
+        let paths: Vec<_> = index
+            .directories
+            .iter()
+            .map(|dir| dir.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            ["48x48/apps", "scalable/apps", "32x32/apps", "48x48@2/apps"]
+        );
+
+        let threshold = directory(&index, "32x32/apps");
+        assert!(threshold.kind == DirectoryType::Threshold);
+        assert_eq!((threshold.scale, threshold.threshold), (1, 2));
+        assert_eq!(directory(&index, "48x48@2/apps").scale, 2);
+    }
+
+    #[test]
+    fn matches_directory_sizes() {
+        let index = ThemeIndex::parse(INDEX);
+
+        let fixed = directory(&index, "48x48/apps");
+        assert!(fixed.matches_size(48, 1));
+        assert!(!fixed.matches_size(47, 1));
+        assert!(!fixed.matches_size(48, 2));
+        assert!(directory(&index, "48x48@2/apps").matches_size(48, 2));
+
+        let scalable = directory(&index, "scalable/apps");
+        assert!(scalable.matches_size(16, 1));
+        assert!(scalable.matches_size(256, 1));
+        assert!(!scalable.matches_size(512, 1));
+
+        let threshold = directory(&index, "32x32/apps");
+        assert!(threshold.matches_size(30, 1));
+        assert!(threshold.matches_size(34, 1));
+        assert!(!threshold.matches_size(35, 1));
+    }
+
+    #[test]
+    fn measures_size_distance() {
+        let index = ThemeIndex::parse(INDEX);
+
+        let fixed = directory(&index, "48x48/apps");
+        assert_eq!(fixed.size_distance(64, 1), 16);
+        assert_eq!(fixed.size_distance(32, 1), 16);
+        // Sizes are compared in pixels, so a 48@2 icon is the closest to 96 pixels
+        assert_eq!(fixed.size_distance(48, 2), 48);
+        assert_eq!(directory(&index, "48x48@2/apps").size_distance(96, 1), 0);
+
+        let scalable = directory(&index, "scalable/apps");
+        assert_eq!(scalable.size_distance(128, 1), 0);
+        assert_eq!(scalable.size_distance(8, 1), 8);
+        assert_eq!(scalable.size_distance(300, 1), 44);
+
+        let threshold = directory(&index, "32x32/apps");
+        assert_eq!(threshold.size_distance(33, 1), 0);
+        assert_eq!(threshold.size_distance(40, 1), 6);
+    }
+}
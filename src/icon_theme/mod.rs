@@ -0,0 +1,250 @@
+mod index;
+
+use index::ThemeIndex;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const DEFAULT_THEME: &str = "hicolor";
+const ICON_EXTENSIONS: [&str; 2] = ["png", "svg"];
+const PIXMAPS_DIR: &str = "/usr/share/pixmaps";
+
+/// Resolves icon names to files according to the freedesktop icon theme specification.
+pub struct IconLookup {
+    theme: String,
+    size: u32,
+    scale: u32,
+    base_dirs: Vec<PathBuf>,
+    themes: HashMap<String, Option<Rc<ThemeIndex>>>,
+}
+
+impl IconLookup {
+    pub fn new(theme: Option<&str>, size: f32, scale: f32) -> Self {
+        let xdg_dirs = xdg::BaseDirectories::new();
+
+        let home_icons = env::var_os("HOME").map(|home| PathBuf::from(home).join(".icons"));
+        let data_icons = xdg_dirs
+            .get_data_home()
+            .into_iter()
+            .chain(xdg_dirs.get_data_dirs())
+            .map(|dir| dir.join("icons"));
+        let base_dirs = home_icons.into_iter().chain(data_icons).collect();
+
+        Self {
+            theme: theme.unwrap_or(DEFAULT_THEME).to_string(),
+            size: size.round().max(1.0) as u32,
+            scale: scale.round().max(1.0) as u32,
+            base_dirs,
+            themes: HashMap::new(),
+        }
+    }
+
+    /// Returns the icon path as is if it contains a path separator, otherwise looks up the icon
+    /// in the configured theme, its parents, `hicolor` and the pixmaps directory.
+    pub fn find_icon(&mut self, icon: &str) -> Option<PathBuf> {
+        if icon.contains('/') {
+            return Some(PathBuf::from(icon));
+        }
+
+        if icon.is_empty() {
+            return None;
+        }
+
+        let theme = self.theme.clone();
+        self.find_in_theme(icon, &theme, &mut Vec::new())
+            .or_else(|| self.find_in_theme(icon, DEFAULT_THEME, &mut Vec::new()))
+            .or_else(|| self.find_fallback_icon(icon))
+    }
+
+    fn find_in_theme(
+        &mut self,
+        icon: &str,
+        theme: &str,
+        visited: &mut Vec<String>,
+    ) -> Option<PathBuf> {
+        if visited.iter().any(|name| name == theme) {
+            return None;
+        }
+        visited.push(theme.to_string());
+
+        let index = self.theme_index(theme)?;
+        if let Some(path) = self.lookup_icon(icon, theme, &index) {
+            return Some(path);
+        }
+
+        index
+            .inherits
+            .iter()
+            .find_map(|parent| self.find_in_theme(icon, parent, visited))
+    }
+
+    fn lookup_icon(&self, icon: &str, theme: &str, index: &ThemeIndex) -> Option<PathBuf> {
+        let candidates = |matches_size: bool| {
+            index
+                .directories
+                .iter()
+                .filter(move |dir| dir.matches_size(self.size, self.scale) == matches_size)
+                .flat_map(move |dir| {
+                    self.base_dirs.iter().flat_map(move |base| {
+                        ICON_EXTENSIONS.iter().map(move |ext| {
+                            let path = base
+                                .join(theme)
+                                .join(&dir.path)
+                                .join(format!("{}.{}", icon, ext));
+                            (dir, path)
+                        })
+                    })
+                })
+                .filter(|(_, path)| path.is_file())
+        };
+
+        if let Some((_, path)) = candidates(true).next() {
+            return Some(path);
+        }
+
+        candidates(false)
+            .min_by_key(|(dir, _)| dir.size_distance(self.size, self.scale))
+            .map(|(_, path)| path)
+    }
+
+    fn find_fallback_icon(&self, icon: &str) -> Option<PathBuf> {
+        self.base_dirs
+            .iter()
+            .map(PathBuf::as_path)
+            .chain([Path::new(PIXMAPS_DIR)])
+            .flat_map(|dir| {
+                ICON_EXTENSIONS
+                    .iter()
+                    .map(move |ext| dir.join(format!("{}.{}", icon, ext)))
+            })
+            .find(|path| path.is_file())
+    }
+
+    fn theme_index(&mut self, theme: &str) -> Option<Rc<ThemeIndex>> {
+        if let Some(index) = self.themes.get(theme) {
+            return index.clone();
+        }
+
+        let index = self
+            .base_dirs
+            .iter()
+            .map(|dir| dir.join(theme).join("index.theme"))
+            .find_map(|path| fs::read_to_string(path).ok())
+            .map(|contents| Rc::new(ThemeIndex::parse(&contents)));
+
+        if index.is_none() {
+            log::warn!("Icon theme `{}` is not found", theme);
+        }
+
+        self.themes.insert(theme.to_string(), index.clone());
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestIcons {
+        dir: PathBuf,
+    }
+
+    impl TestIcons {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("gpcl-icons-{}-{}", name, std::process::id()));
+            _ = fs::remove_dir_all(&dir);
+            Self { dir }
+        }
+
+        fn theme(&self, theme: &str, index: &str) -> &Self {
+            fs::create_dir_all(self.dir.join(theme)).unwrap();
+            fs::write(self.dir.join(theme).join("index.theme"), index).unwrap();
+            self
+        }
+
+        fn icon(&self, path: &str) -> PathBuf {
+            let path = self.dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+            path
+        }
+
+        fn lookup(&self, theme: &str, size: u32) -> IconLookup {
+            IconLookup {
+                theme: theme.to_string(),
+                size,
+                scale: 1,
+                base_dirs: vec![self.dir.clone()],
+                themes: HashMap::new(),
+            }
+        }
+    }
+
+    impl Drop for TestIcons {
+        fn drop(&mut self) {
+            _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    const FIXED_SIZES: &str = "\
+[Icon Theme]
+Directories=32x32/apps,48x48/apps,128x128/apps
+[32x32/apps]
+Size=32
+Type=Fixed
+[48x48/apps]
+Size=48
+Type=Fixed
+[128x128/apps]
+Size=128
+Type=Fixed
+";
+
+    #[test]
+    fn looks_up_icons_in_parent_themes_and_hicolor() {
+        let icons = TestIcons::new("inherits");
+        icons
+            .theme(
+                "Child",
+                "[Icon Theme]\nInherits=Parent\nDirectories=48x48/apps\n[48x48/apps]\nSize=48\n",
+            )
+            .theme("Parent", FIXED_SIZES)
+            .theme("hicolor", FIXED_SIZES);
+        let own = icons.icon("Child/48x48/apps/kodi.png");
+        let inherited = icons.icon("Parent/48x48/apps/steam.svg");
+        let fallback = icons.icon("hicolor/48x48/apps/retroarch.png");
+        let pixmap = icons.icon("doom.png");
+
+        let mut lookup = icons.lookup("Child", 48);
+        assert_eq!(lookup.find_icon("kodi"), Some(own));
+        assert_eq!(lookup.find_icon("steam"), Some(inherited));
+        assert_eq!(lookup.find_icon("retroarch"), Some(fallback));
+        assert_eq!(lookup.find_icon("doom"), Some(pixmap));
+        assert_eq!(lookup.find_icon("gpcl-missing-icon"), None);
+        assert_eq!(
+            lookup.find_icon("/opt/icon.png"),
+            Some(PathBuf::from("/opt/icon.png"))
+        );
+    }
+
+    #[test]
+    fn selects_the_closest_size() {
+        let icons = TestIcons::new("sizes");
+        icons.theme("hicolor", FIXED_SIZES);
+        let small = icons.icon("hicolor/32x32/apps/kodi.png");
+        icons.icon("hicolor/48x48/apps/kodi.png");
+        let large = icons.icon("hicolor/128x128/apps/kodi.png");
+
+        assert_eq!(icons.lookup("hicolor", 32).find_icon("kodi"), Some(small));
+        assert_eq!(icons.lookup("hicolor", 100).find_icon("kodi"), Some(large));
+        let exact = icons.dir.join("hicolor/48x48/apps/kodi.png");
+        assert_eq!(
+            icons.lookup("hicolor", 48).find_icon("kodi"),
+            Some(exact.clone())
+        );
+        assert_eq!(icons.lookup("hicolor", 64).find_icon("kodi"), Some(exact));
+    }
+}
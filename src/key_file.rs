@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+/// Minimal parser for the freedesktop key file format used by desktop entries and icon themes.
+/// Values are kept raw, localized keys are stored as is (e.g. `Name[de]`).
+#[derive(Default)]
+pub struct KeyFile {
+    groups: HashMap<String, HashMap<String, String>>,
+}
+
+impl KeyFile {
+    pub fn parse(contents: &str) -> Self {
+        let mut groups: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut group: Option<&mut HashMap<String, String>> = None;
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                group = Some(groups.entry(name.to_string()).or_default());
+                continue;
+            }
+
+            let (Some(group), Some((key, value))) = (group.as_mut(), line.split_once('=')) else {
+                continue;
+            };
+
+            group
+                .entry(key.trim().to_string())
+                .or_insert_with(|| value.trim().to_string());
+        }
+
+        Self { groups }
+    }
+
+    pub fn get(&self, group: &str, key: &str) -> Option<&str> {
+        self.groups.get(group)?.get(key).map(String::as_str)
+    }
+
+    pub fn get_bool(&self, group: &str, key: &str) -> bool {
+        self.get(group, key) == Some("true")
+    }
+
+    pub fn get_u32(&self, group: &str, key: &str) -> Option<u32> {
+        self.get(group, key)?.parse().ok()
+    }
+
+    pub fn get_list(&self, group: &str, key: &str) -> Vec<&str> {
+        self.get(group, key)
+            .map(|value| value.split(',').map(str::trim).filter(|s| !s.is_empty()))
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_groups_and_values() {
+        let key_file = KeyFile::parse(
+            "Ignored=outside of groups\n\
+             # Comment\n\
+             [Desktop Entry]\n\
+             Name = Steam \n\
+             Name[de]=Dampf\n\
+             Name=Duplicate\n\
+             NoDisplay=true\n\
+             \n\
+             [Icon Theme]\n\
+             Size=48\n\
+             Inherits=Adwaita, ,hicolor\n\
+             Broken line\n",
+        );
+
+        assert_eq!(key_file.get("Desktop Entry", "Name"), Some("Steam"));
+        assert_eq!(key_file.get("Desktop Entry", "Name[de]"), Some("Dampf"));
+        assert_eq!(key_file.get("Desktop Entry", "Ignored"), None);
+        assert_eq!(key_file.get("Icon Theme", "Name"), None);
+        assert_eq!(key_file.get("Missing", "Name"), None);
+
+        assert!(key_file.get_bool("Desktop Entry", "NoDisplay"));
+        assert!(!key_file.get_bool("Desktop Entry", "Hidden"));
+        assert_eq!(key_file.get_u32("Icon Theme", "Size"), Some(48));
+        assert_eq!(key_file.get_u32("Desktop Entry", "Name"), None);
+        assert_eq!(
+            key_file.get_list("Icon Theme", "Inherits"),
+            ["Adwaita", "hicolor"]
+        );
+        assert!(key_file.get_list("Icon Theme", "Directories").is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::launcher::Executable;
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const LOGS_DIR: &str = "logs";
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+const MAX_ROTATED_LOGS: usize = 3;
+const MAX_TAIL_LINES: usize = 5;
+const READERS_TIMEOUT: Duration = Duration::from_secs(1);
+
+type FinishCallback = Box<dyn FnOnce(Vec<String>) + Send>;
+
+enum LogEvent {
+    Line(Vec<u8>),
+    Finish {
+        footer: String,
+        on_finished: FinishCallback,
+    },
+}
+
+/// Log file of a launched application that receives its piped stdout and stderr.
+/// The file is opened, rotated and written by a background thread,
+/// so the event loop doesn't wait for the disk.
+pub struct ChildLog {
+    events: Sender<LogEvent>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl ChildLog {
+    /// Starts writing the log with a header describing the launch
+    /// and copying piped stdout and stderr of the child into it.
+    pub fn start(exec: &Executable, child: &mut Child) -> Self {
+        let (events, rx) = mpsc::channel();
+        let name = exec.name.clone();
+        let header = header(exec);
+        thread::spawn(move || write_log(&name, header, rx));
+
+        let mut log = Self {
+            events,
+            readers: Vec::new(),
+        };
+        if let Some(stdout) = child.stdout.take() {
+            log.spawn_reader(stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            log.spawn_reader(stderr);
+        }
+        log
+    }
+
+    /// Writes the exit status after the captured output is written and passes the last output
+    /// lines to the callback. Both happen in background: readers finish only when all processes
+    /// holding the pipes exit, so they are waited for a limited time.
+    /// Output written after that isn't logged, as the log may belong to the next launch then.
+    pub fn finish(
+        self,
+        status: ExitStatus,
+        on_finished: impl FnOnce(Vec<String>) + Send + 'static,
+    ) {
+        let footer = format!(
+            "=== {} === {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            status
+        );
+
+        thread::spawn(move || {
+            let started_at = Instant::now();
+            while self.readers.iter().any(|r| !r.is_finished())
+                && started_at.elapsed() < READERS_TIMEOUT
+            {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            _ = self.events.send(LogEvent::Finish {
+                footer,
+                on_finished: Box::new(on_finished),
+            });
+        });
+    }
+
+    fn spawn_reader(&mut self, stream: impl Read + Send + 'static) {
+        let events = self.events.clone();
+        let reader = thread::spawn(move || {
+            let mut reader = BufReader::new(stream);
+            let mut line = Vec::new();
+
+            // The pipe is read until it's closed even when the log is finished,
+            // so processes left by the item don't block on writing to it
+            while let Ok(len) = reader.read_until(b'\n', &mut line) {
+                if len == 0 {
+                    break;
+                }
+                _ = events.send(LogEvent::Line(std::mem::take(&mut line)));
+            }
+        });
+
+        self.readers.push(reader);
+    }
+}
+
+fn header(exec: &Executable) -> String {
+    let mut header = format!(
+        "\n=== {} ===\ncommand: {:?}\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        exec.command_line()
+    );
+
+    if let Some(cwd) = &exec.cwd {
+        header += &format!("cwd: {}\n", cwd.display());
+    }
+    for (name, value) in &exec.env {
+        header += &format!("env: {}={}\n", name, value);
+    }
+    for name in &exec.env_remove {
+        header += &format!("env: -{}\n", name);
+    }
+    header
+}
+
+/// Writes events of the log until it's finished.
+/// The last lines are kept for the failure report even if the file can't be opened.
+fn write_log(item_name: &str, header: String, events: Receiver<LogEvent>) {
+    let mut file = open_log(item_name)
+        .map_err(|error| log::error!("Failed to open log of `{}`: {}", item_name, error))
+        .ok();
+    let mut tail = VecDeque::with_capacity(MAX_TAIL_LINES);
+
+    if let Some(file) = &mut file {
+        file.write(header.as_bytes());
+    }
+
+    for event in events {
+        match event {
+            LogEvent::Line(line) => {
+                if let Some(file) = &mut file {
+                    file.write(&line);
+                }
+                if tail.len() == MAX_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+            }
+            LogEvent::Finish {
+                footer,
+                on_finished,
+            } => {
+                if let Some(file) = &mut file {
+                    file.write(footer.as_bytes());
+                }
+                on_finished(tail.into());
+                return;
+            }
+        }
+    }
+}
+
+fn open_log(item_name: &str) -> io::Result<RotatingFile> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix(env!("CARGO_PKG_NAME"));
+    let dir = xdg_dirs.create_state_directory(LOGS_DIR)?;
+    RotatingFile::open(dir.join(format!("{}.log", sanitize_file_name(item_name))))
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        if self.size >= MAX_LOG_SIZE {
+            if let Err(error) = self.rotate() {
+                log::error!("Failed to rotate `{}`: {}", self.path.display(), error);
+            }
+        }
+
+        match self.file.write_all(data) {
+            Ok(_) => self.size += data.len() as u64,
+            Err(error) => log::error!("Failed to write `{}`: {}", self.path.display(), error),
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |idx: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", idx));
+            PathBuf::from(path)
+        };
+
+        for idx in (1..MAX_ROTATED_LOGS).rev() {
+            let from = rotated(idx);
+            if from.exists() {
+                fs::rename(from, rotated(idx + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated(1))?;
+
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
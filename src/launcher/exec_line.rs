@@ -0,0 +1,226 @@
+use crate::expand::{expand_variable, home_dir, is_tilde_end, ExpandError};
+
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum ExecLineError {
+    UnterminatedSingleQuote,
+    UnterminatedDoubleQuote,
+    TrailingBackslash,
+    Expand(ExpandError),
+}
+
+impl From<ExpandError> for ExecLineError {
+    fn from(error: ExpandError) -> Self {
+        Self::Expand(error)
+    }
+}
+
+impl fmt::Display for ExecLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedSingleQuote => write!(f, "unterminated single quote"),
+            Self::UnterminatedDoubleQuote => write!(f, "unterminated double quote"),
+            Self::TrailingBackslash => write!(f, "trailing backslash"),
+            Self::Expand(error) => error.fmt(f),
+        }
+    }
+}
+
+/// Splits the line into words following POSIX shell quoting rules.
+/// Leading `~` and variable references are expanded outside single quotes,
+/// but expanded values are never split into several words.
+/// Other expansions and operators are not supported: `|`, `;` etc. are kept literally.
+pub fn split_words(line: &str) -> Result<Vec<String>, ExecLineError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(ExecLineError::UnterminatedSingleQuote),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('\n') => {}
+                            Some(c @ ('$' | '`' | '"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(ExecLineError::UnterminatedDoubleQuote),
+                        },
+                        Some('$') => expand_variable(&mut chars, &mut word)?,
+                        Some(c) => word.push(c),
+                        None => return Err(ExecLineError::UnterminatedDoubleQuote),
+                    }
+                }
+            }
+            '~' if !in_word && is_tilde_end(chars.peek()) => {
+                in_word = true;
+                word.push_str(&home_dir()?);
+            }
+            '$' => {
+                in_word = true;
+                expand_variable(&mut chars, &mut word)?;
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => {
+                    in_word = true;
+                    word.push(c);
+                }
+                None => return Err(ExecLineError::TrailingBackslash),
+            },
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Vec<String> {
+        split_words(line).unwrap()
+    }
+
+    #[test]
+    fn splits_on_any_whitespace() {
+        assert_eq!(split("  steam \t -gamepadui\n"), ["steam", "-gamepadui"]);
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn keeps_single_quoted_text_literally() {
+        assert_eq!(
+            split(r#"sh -c 'kodi --standalone "$HOME" \n'"#),
+            ["sh", "-c", r#"kodi --standalone "$HOME" \n"#]
+        );
+    }
+
+    #[test]
+    fn handles_escapes_in_double_quotes() {
+        assert_eq!(
+            split(r#"echo "a \"b\" \\ \$c \d""#),
+            ["echo", r#"a "b" \ $c \d"#]
+        );
+    }
+
+    #[test]
+    fn handles_escapes_outside_quotes() {
+        assert_eq!(
+            split(r"/opt/My\ Games/run \'x\' \\"),
+            ["/opt/My Games/run", "'x'", "\\"]
+        );
+    }
+
+    #[test]
+    fn joins_adjacent_quoted_parts() {
+        assert_eq!(split(r#"--opt='a b'"c d"e"#), ["--opt=a bc de"]);
+    }
+
+    #[test]
+    fn keeps_empty_quoted_words() {
+        assert_eq!(split(r#"prog '' """#), ["prog", "", ""]);
+    }
+
+    #[test]
+    fn removes_line_continuations() {
+        assert_eq!(split("prog \\\n  arg \"a\\\nb\""), ["prog", "arg", "ab"]);
+    }
+
+    #[test]
+    fn expands_variables_outside_single_quotes() {
+        std::env::set_var("GPCL_TEST_DIR", "/opt/my games");
+        std::env::set_var("GPCL_TEST_EMPTY", "");
+
+        assert_eq!(
+            split(r#"$GPCL_TEST_DIR/run "${GPCL_TEST_DIR}/cfg" '$GPCL_TEST_DIR' \$GPCL_TEST_DIR"#),
+            [
+                "/opt/my games/run",
+                "/opt/my games/cfg",
+                "$GPCL_TEST_DIR",
+                "$GPCL_TEST_DIR"
+            ]
+        );
+        assert_eq!(
+            split("${GPCL_TEST_UNDEFINED:-a b} ${GPCL_TEST_EMPTY:-c} x$GPCL_TEST_EMPTY $ 5$"),
+            ["a b", "c", "x", "$", "5$"]
+        );
+    }
+
+    #[test]
+    fn expands_leading_tilde() {
+        let home = std::env::var("HOME").unwrap();
+
+        assert_eq!(
+            split("~/bin/emu ~ a~ '~' ~user"),
+            [
+                format!("{}/bin/emu", home),
+                home,
+                "a~".into(),
+                "~".into(),
+                "~user".into()
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_undefined_variables() {
+        assert_eq!(
+            split_words("run $GPCL_TEST_UNDEFINED"),
+            Err(ExecLineError::Expand(ExpandError::UndefinedVariable(
+                "GPCL_TEST_UNDEFINED".into()
+            )))
+        );
+        assert_eq!(
+            split_words("run ${GPCL_TEST_UNDEFINED"),
+            Err(ExecLineError::Expand(ExpandError::UnterminatedBrace))
+        );
+    }
+
+    #[test]
+    fn reports_unbalanced_quotes() {
+        assert_eq!(
+            split_words("sh -c 'kodi"),
+            Err(ExecLineError::UnterminatedSingleQuote)
+        );
+        assert_eq!(
+            split_words(r#"echo "a\""#),
+            Err(ExecLineError::UnterminatedDoubleQuote)
+        );
+        assert_eq!(
+            split_words("echo a\\"),
+            Err(ExecLineError::TrailingBackslash)
+        );
+    }
+}
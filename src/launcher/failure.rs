@@ -0,0 +1,39 @@
+use nix::sys::signal::Signal;
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+
+/// Describes an application that failed to start or exited abnormally.
+pub struct LaunchFailure {
+    pub name: String,
+    pub reason: String,
+    pub output: Vec<String>,
+}
+
+pub fn describe_spawn_error(program: &str, error: &io::Error) -> String {
+    match error.kind() {
+        io::ErrorKind::NotFound => format!("Program `{}` is not found", program),
+        io::ErrorKind::PermissionDenied => format!("Permission denied to run `{}`", program),
+        _ => format!("Failed to run `{}`: {}", program, error),
+    }
+}
+
+/// Returns `None` if the process exited successfully.
+pub fn describe_exit_status(status: ExitStatus) -> Option<String> {
+    if let Some(signal) = status.signal() {
+        let signal_name = Signal::try_from(signal)
+            .map(|signal| signal.as_str().to_string())
+            .unwrap_or_else(|_| format!("signal {}", signal));
+        let core_dumped = if status.core_dumped() {
+            " (core dumped)"
+        } else {
+            ""
+        };
+        return Some(format!("Killed by {}{}", signal_name, core_dumped));
+    }
+
+    match status.code() {
+        Some(0) | None => None,
+        Some(code) => Some(format!("Exited with code {}", code)),
+    }
+}
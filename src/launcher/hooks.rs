@@ -0,0 +1,119 @@
+use crate::launcher::failure::{describe_exit_status, describe_spawn_error};
+use crate::launcher::Executable;
+
+use nix::errno::Errno;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::ExitStatus;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Hooks running in background, the result is an error of the first failed hook.
+pub type RunningHooks = JoinHandle<Result<(), String>>;
+
+/// Runs the hooks one by one in background. The rest of the hooks are skipped
+/// once a hook fails or doesn't finish in time.
+pub fn spawn_hooks(
+    hooks: Vec<Executable>,
+    vars: Vec<(String, String)>,
+    timeout: Duration,
+) -> RunningHooks {
+    thread::spawn(move || {
+        hooks
+            .iter()
+            .try_for_each(|hook| run_hook(hook, &vars, timeout))
+    })
+}
+
+pub fn join_hooks(hooks: RunningHooks) -> Result<(), String> {
+    hooks
+        .join()
+        .unwrap_or_else(|_| Err("Hooks thread panicked".into()))
+}
+
+/// Describes the item to hooks, the exit status is passed to post-exit hooks.
+pub fn hook_vars(
+    exec: &Executable,
+    idx: usize,
+    status: Option<ExitStatus>,
+) -> Vec<(String, String)> {
+    let mut vars = vec![
+        ("GPCL_ITEM_NAME".into(), exec.name.clone()),
+        ("GPCL_ITEM_INDEX".into(), idx.to_string()),
+    ];
+
+    if let Some(code) = status.and_then(|status| status.code()) {
+        vars.push(("GPCL_EXIT_CODE".into(), code.to_string()));
+    }
+    if let Some(signal) = status.and_then(|status| status.signal()) {
+        vars.push(("GPCL_EXIT_SIGNAL".into(), signal.to_string()));
+    }
+
+    vars
+}
+
+fn run_hook(hook: &Executable, vars: &[(String, String)], timeout: Duration) -> Result<(), String> {
+    let command_line = hook.command_line().join(" ");
+    log::info!("Running hook `{}`", command_line);
+
+    let mut child = hook
+        .command()
+        .and_then(|mut command| {
+            // Processes started by shell hooks are killed along with the hook on timeout
+            command.envs(vars.iter().cloned()).process_group(0).spawn()
+        })
+        .map_err(|error| describe_spawn_error(&hook.program, &error))?;
+
+    let started_at = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                return match describe_exit_status(status) {
+                    Some(reason) => Err(format!("`{}`: {}", command_line, reason)),
+                    None => Ok(()),
+                }
+            }
+            Ok(None) if started_at.elapsed() >= timeout => {
+                let pgid = Pid::from_raw(child.id() as i32);
+                match killpg(pgid, Signal::SIGKILL) {
+                    Ok(()) | Err(Errno::ESRCH) => {}
+                    Err(error) => log::error!("Failed to kill hook group {}: {}", pgid, error),
+                }
+                _ = child.wait();
+                return Err(format!(
+                    "`{}` doesn't finish in {:?}",
+                    command_line, timeout
+                ));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(error) => return Err(format!("`{}`: {}", command_line, error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExecConfig;
+    use std::fs;
+
+    #[test]
+    fn kills_hook_processes_on_timeout() {
+        let pid_file = std::env::temp_dir().join(format!("gpcl-hook-{}", std::process::id()));
+        let line = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let hook = Executable::new(&ExecConfig::Line(line), true).unwrap();
+
+        let result = run_hook(&hook, &[], Duration::from_millis(200));
+        assert!(result.unwrap_err().contains("doesn't finish"));
+
+        let pid = fs::read_to_string(&pid_file).unwrap();
+        _ = fs::remove_file(&pid_file);
+        thread::sleep(Duration::from_millis(100));
+        // The background process is killed, it remains a zombie until it's reaped by init
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        assert!(stat.is_empty() || stat.contains(") Z "));
+    }
+}
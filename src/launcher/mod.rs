@@ -1,76 +1,1065 @@
+mod child_log;
+mod exec_line;
+mod failure;
+mod hooks;
 mod model;
+mod order;
+mod process_tree;
+mod supervisor;
 
-use crate::launcher::model::config_entry_into_item;
-use crate::{config::AppIconConfig, AppIconModel};
-use model::Executable;
+use crate::config::{
+    AppIconConfig, Diagnostic, Diagnostics, ForceQuitConfig, ItemKind, KioskConfig, LaunchConfig,
+    LayoutConfig, SortOrder, WrapperConfig,
+};
+use crate::control::ItemInfo;
+use crate::gamepad_manager::ButtonChord;
+use crate::icon_theme::IconLookup;
+use crate::launcher::child_log::ChildLog;
+use crate::launcher::failure::{describe_exit_status, describe_spawn_error};
+use crate::launcher::hooks::{hook_vars, join_hooks, spawn_hooks, RunningHooks};
+use crate::launcher::model::{config_entry_into_item, find_exec, find_item, find_item_path, Item};
+use crate::launcher::order::{recent_names, sort_entries};
+use crate::launcher::process_tree::ItemProcesses;
+use crate::launcher::supervisor::{ExitAction, Supervisor};
+use crate::logind::{Logind, PowerAction};
+use crate::play_time::{Session, SessionRecorder};
+use crate::state::{Selection, StateStore};
+use crate::AppIconModel;
+pub use failure::LaunchFailure;
+pub use model::{item_wrappers, AppAction, Executable};
+pub use process_tree::become_subreaper;
+pub use supervisor::RestartOptions;
 
-use slint::VecModel;
-use std::process::{Child, Command};
+use nix::sys::signal::Signal;
+use slint::{Model, VecModel};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, ExitStatus, Stdio};
 use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_KILL_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const RECENT_FOLDER_NAME: &str = "Recent";
+const RECENT_FOLDER_ICON: &str = "document-open-recent";
+
+/// The item that is started once its pre-launch hooks succeed.
+struct PendingLaunch {
+    exec: Executable,
+    idx: usize,
+    hooks: RunningHooks,
+}
+
+struct RunningChild {
+    exec: Executable,
+    idx: usize,
+    process: Child,
+    processes: ItemProcesses,
+    /// Start of the play session, it's taken when the session is recorded
+    started_at: Option<SystemTime>,
+    log: Option<ChildLog>,
+    /// Exit status of the launched process, its descendants may still be running
+    status: Option<ExitStatus>,
+    /// Time of the force quit request, `killed` is set once it is escalated to `SIGKILL`
+    terminating_since: Option<Instant>,
+    killed: bool,
+}
+
+impl RunningChild {
+    /// Records the play time once, the session ends when the item exits or is force quit.
+    fn end_session(&mut self, sessions: &mut SessionRecorder) {
+        if let Some(started_at) = self.started_at.take() {
+            sessions.record(Session::new(&self.exec.name, started_at, SystemTime::now()));
+        }
+    }
+}
+
+/// Opened folder with the item selected in it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FolderLevel {
+    idx: usize,
+    selected: usize,
+}
 
 pub struct Launcher {
-    items: Vec<Executable>,
+    items: Vec<(AppIconModel, Item)>,
+    /// Opened folders, starting from the top level
+    folder_path: Vec<FolderLevel>,
+    /// Folders closed by going back, the last one closed first.
+    /// Opening them again restores their selection.
+    closed_folders: Vec<FolderLevel>,
+    /// Icons of the current list
     item_icons: Rc<VecModel<AppIconModel>>,
-    child_process: Option<Child>,
+    state: Rc<RefCell<StateStore>>,
+    sort: SortOrder,
+    recent_items: usize,
+    recent_icon: Option<AppIconModel>,
+    /// The "Recent" folder is the first top level item
+    has_recent: bool,
+    pending: Option<PendingLaunch>,
+    child: Option<RunningChild>,
+    post_hooks: Option<RunningHooks>,
+    hook_timeout: Duration,
+    force_quit_chord: ButtonChord,
+    kill_timeout: Duration,
+    supervisor: Option<Supervisor>,
+    /// Availability of power actions, it's queried in background once they are configured
+    power_actions: Option<HashMap<PowerAction, bool>>,
+    power_actions_rx: Option<Receiver<HashMap<PowerAction, bool>>>,
+    sessions: SessionRecorder,
+    failures_tx: Sender<LaunchFailure>,
+    failures_rx: Receiver<LaunchFailure>,
 }
 
 impl Launcher {
-    pub fn new() -> Self {
+    pub fn new(state: Rc<RefCell<StateStore>>) -> Self {
+        let (failures_tx, failures_rx) = mpsc::channel();
+
         Self {
             items: Vec::new(),
+            folder_path: Vec::new(),
+            closed_folders: Vec::new(),
             item_icons: Rc::new(VecModel::default()),
-            child_process: None,
+            state,
+            sort: SortOrder::default(),
+            recent_items: 0,
+            recent_icon: None,
+            has_recent: false,
+            pending: None,
+            child: None,
+            post_hooks: None,
+            hook_timeout: DEFAULT_HOOK_TIMEOUT,
+            force_quit_chord: ButtonChord::default(),
+            kill_timeout: DEFAULT_KILL_TIMEOUT,
+            supervisor: None,
+            power_actions: None,
+            power_actions_rx: None,
+            sessions: SessionRecorder::default(),
+            failures_tx,
+            failures_rx,
+        }
+    }
+
+    pub fn reset_items(
+        &mut self,
+        items: &[AppIconConfig],
+        layout: &LayoutConfig,
+        launch: &LaunchConfig,
+        wrappers: &BTreeMap<String, WrapperConfig>,
+        icons: &mut IconLookup,
+        diagnostics: &mut Diagnostics,
+    ) {
+        self.hook_timeout = DEFAULT_HOOK_TIMEOUT;
+        if let Some(timeout) = launch.hook_timeout {
+            match Duration::try_from_secs_f32(timeout) {
+                Ok(timeout) => self.hook_timeout = timeout,
+                Err(_) => {
+                    let message = format!("Invalid hook timeout `{}`", timeout);
+                    diagnostics.push(Diagnostic::warning(message));
+                }
+            }
+        }
+
+        let mut entries: Vec<_> = items
+            .iter()
+            .map(|item| config_entry_into_item(item, launch, wrappers, icons))
+            .collect();
+        if update_availability(&mut entries, self.power_actions.as_ref()) {
+            self.query_power_actions();
+        }
+
+        self.sort = layout.sort.unwrap_or_default();
+        self.recent_items = layout.recent_items.unwrap_or_default();
+        self.recent_icon = (self.recent_items > 0).then(|| {
+            let config = AppIconConfig {
+                name: RECENT_FOLDER_NAME.into(),
+                icon: RECENT_FOLDER_ICON.into(),
+                kind: ItemKind::Folder,
+                ..Default::default()
+            };
+            config_entry_into_item(&config, launch, wrappers, icons).0
+        });
+
+        self.items = entries;
+        self.has_recent = false;
+        self.apply_order();
+        self.close_all_folders();
+    }
+
+    /// Reorders the items after a launch if the order depends on launches.
+    /// The top level list is shown then, so the selection should be restored.
+    pub fn update_order(&mut self) -> bool {
+        if self.sort != SortOrder::Recent
+            && self.sort != SortOrder::MostUsed
+            && self.recent_icon.is_none()
+        {
+            return false;
         }
+
+        self.apply_order();
+        self.close_all_folders();
+        true
     }
 
-    pub fn reset_items(&mut self, items: &[AppIconConfig]) {
-        self.items.clear();
-        self.item_icons.clear();
+    /// Sorts the items and fills the "Recent" folder with the last launched items.
+    fn apply_order(&mut self) {
+        if self.has_recent {
+            self.items.remove(0);
+            self.has_recent = false;
+        }
+
+        let state = self.state.borrow();
+        sort_entries(&mut self.items, self.sort, state.launches());
 
-        for (icon, item) in items.iter().map(config_entry_into_item) {
-            self.items.push(item);
-            self.item_icons.push(icon);
+        let Some(icon) = &self.recent_icon else {
+            return;
+        };
+        // Launched items may be removed from the config since then
+        let entries: Vec<_> = recent_names(state.launches())
+            .into_iter()
+            .filter_map(|name| find_item(&self.items, name).cloned())
+            .take(self.recent_items)
+            .collect();
+
+        if !entries.is_empty() {
+            let name = RECENT_FOLDER_NAME.to_string();
+            self.items
+                .insert(0, (icon.clone(), Item::Folder { name, entries }));
+            self.has_recent = true;
         }
     }
 
+    /// Starts checking power actions with logind, so the UI isn't blocked by D-Bus calls.
+    fn query_power_actions(&mut self) {
+        if self.power_actions.is_some() || self.power_actions_rx.is_some() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || _ = tx.send(Logind::query_availability()));
+        self.power_actions_rx = Some(rx);
+    }
+
+    /// Grays out power actions that are not allowed once logind replies.
+    pub fn poll_power_actions(&mut self) {
+        let Some(rx) = &self.power_actions_rx else {
+            return;
+        };
+        let Ok(power_actions) = rx.try_recv() else {
+            return;
+        };
+
+        update_availability(&mut self.items, Some(&power_actions));
+        self.power_actions = Some(power_actions);
+        self.power_actions_rx = None;
+
+        for (idx, (icon, _)) in self.entries().iter().enumerate() {
+            self.item_icons.set_row_data(idx, icon.clone());
+        }
+    }
+
+    pub fn set_force_quit_options(
+        &mut self,
+        config: &ForceQuitConfig,
+        diagnostics: &mut Diagnostics,
+    ) {
+        self.force_quit_chord = ButtonChord::from_config(config, diagnostics);
+        self.kill_timeout = DEFAULT_KILL_TIMEOUT;
+
+        if let Some(timeout) = config.kill_timeout {
+            match Duration::try_from_secs_f32(timeout) {
+                Ok(timeout) => self.kill_timeout = timeout,
+                Err(_) => {
+                    let message = format!("Invalid force quit kill timeout `{}`", timeout);
+                    diagnostics.push(Diagnostic::warning(message));
+                }
+            }
+        }
+    }
+
+    /// Sets up autostart of the kiosk item. Changed options of the same item are applied
+    /// without restarting it.
+    pub fn set_kiosk_options(&mut self, config: &KioskConfig, diagnostics: &mut Diagnostics) {
+        let Some(item) = &config.autostart else {
+            self.supervisor = None;
+            return;
+        };
+
+        if find_exec(&self.items, item).is_none() {
+            let message = format!("Kiosk item `{}` is not found", item);
+            diagnostics.push(Diagnostic::warning(message));
+            self.supervisor = None;
+            return;
+        }
+
+        let options = RestartOptions::from_config(config, diagnostics);
+        match &mut self.supervisor {
+            Some(supervisor) if supervisor.item() == item => supervisor.set_options(options),
+            _ => self.supervisor = Some(Supervisor::new(item.clone(), options, Instant::now())),
+        }
+    }
+
+    pub fn force_quit_chord(&self) -> &ButtonChord {
+        &self.force_quit_chord
+    }
+
     pub fn model(&self) -> Rc<VecModel<AppIconModel>> {
         self.item_icons.clone()
     }
 
-    pub fn exec_item(&mut self, idx: usize) {
+    /// Names of the opened folders and items of the current list.
+    fn walk_folders(&self) -> (Vec<&str>, &[(AppIconModel, Item)]) {
+        let mut names = Vec::new();
+        let mut entries = self.items.as_slice();
+        for level in &self.folder_path {
+            match entries.get(level.idx) {
+                Some((
+                    _,
+                    Item::Folder {
+                        name,
+                        entries: items,
+                    },
+                )) => {
+                    names.push(name.as_str());
+                    entries = items;
+                }
+                _ => break,
+            }
+        }
+        (names, entries)
+    }
+
+    /// Items of the current list.
+    fn entries(&self) -> &[(AppIconModel, Item)] {
+        self.walk_folders().1
+    }
+
+    fn update_model(&self) {
+        let icons: Vec<_> = self
+            .entries()
+            .iter()
+            .map(|(icon, _)| icon.clone())
+            .collect();
+        self.item_icons.set_vec(icons);
+    }
+
+    /// Opens the folder and returns the index of the item to select in it.
+    /// The selection is restored if the folder is opened again after going back from it.
+    pub fn open_folder(&mut self, idx: usize) -> usize {
+        let Some((_, Item::Folder { name, .. })) = self.entries().get(idx) else {
+            return 0;
+        };
+        log::info!("Open folder `{}`", name);
+
+        let level = match self.closed_folders.pop() {
+            Some(level) if level.idx == idx => level,
+            _ => {
+                self.closed_folders.clear();
+                FolderLevel { idx, selected: 0 }
+            }
+        };
+        self.folder_path.push(level);
+        self.update_model();
+        level.selected
+    }
+
+    /// Returns to the parent list, the index of the closed folder is returned to select it.
+    pub fn close_folder(&mut self) -> Option<usize> {
+        let level = self.folder_path.pop()?;
+        self.closed_folders.push(level);
+        self.update_model();
+        Some(level.idx)
+    }
+
+    fn close_all_folders(&mut self) {
+        self.folder_path.clear();
+        self.closed_folders.clear();
+        self.update_model();
+    }
+
+    /// Remembers the item selected in the current folder.
+    fn set_selected(&mut self, idx: usize) {
+        if let Some(level) = self.folder_path.last_mut() {
+            level.selected = idx;
+        }
+    }
+
+    /// Selects the item of the current list and saves the selection in the state.
+    pub fn select(&mut self, idx: usize) {
+        self.set_selected(idx);
+        let selection = self.selection(idx);
+        self.state.borrow_mut().set_selection(selection);
+    }
+
+    /// Names of the opened folders.
+    pub fn breadcrumbs(&self) -> Vec<&str> {
+        self.walk_folders().0
+    }
+
+    /// Opens the folder containing the item and returns the index of the item in it.
+    /// Items are looked up outside of the "Recent" folder first.
+    pub fn reveal_item(&mut self, name: &str) -> Option<usize> {
+        let skipped = usize::from(self.has_recent);
+        let path = match find_item_path(&self.items[skipped..], name) {
+            Some(mut path) => {
+                path[0] += skipped;
+                path
+            }
+            None => find_item_path(&self.items, name)?,
+        };
+
+        let (&idx, folders) = path.split_last()?;
+        self.closed_folders.clear();
+        self.folder_path = folders
+            .iter()
+            .zip(&path[1..])
+            .map(|(&idx, &selected)| FolderLevel { idx, selected })
+            .collect();
+        self.update_model();
+        Some(idx)
+    }
+
+    /// Names of all items, folders include their items.
+    pub fn items_info(&self) -> Vec<ItemInfo> {
+        fn describe(entries: &[(AppIconModel, Item)]) -> Vec<ItemInfo> {
+            entries
+                .iter()
+                .map(|(icon, item)| ItemInfo {
+                    name: item.name().to_string(),
+                    enabled: icon.enabled,
+                    items: match item {
+                        Item::Folder { entries, .. } => Some(describe(entries)),
+                        _ => None,
+                    },
+                })
+                .collect()
+        }
+        describe(&self.items)
+    }
+
+    /// Name of the running item.
+    pub fn running_item(&self) -> Option<&str> {
+        self.child.as_ref().map(|child| child.exec.name.as_str())
+    }
+
+    /// Identifies the item of the current list by names to find it after the items are changed.
+    pub fn selection(&self, idx: usize) -> Selection {
+        let (folders, entries) = self.walk_folders();
+        Selection {
+            folders: folders.into_iter().map(String::from).collect(),
+            item: entries.get(idx).map(|(_, item)| item.name().to_string()),
+            index: idx,
+            recent: self.has_recent && self.folder_path.first().is_some_and(|level| level.idx == 0),
+        }
+    }
+
+    /// Opens folders of the selection and returns the index of the selected item.
+    /// A removed item is replaced by the one that took its place,
+    /// the first item of the parent list is selected if a folder is removed.
+    pub fn restore_selection(&mut self, selection: &Selection) -> usize {
+        self.folder_path.clear();
+        self.closed_folders.clear();
+
+        for (depth, name) in selection.folders.iter().enumerate() {
+            // The "Recent" folder is always the first one, configured folders may have its name
+            let folder = if depth == 0 && selection.recent {
+                self.has_recent.then_some(0)
+            } else {
+                let skipped = usize::from(depth == 0 && self.has_recent);
+                self.entries()
+                    .iter()
+                    .enumerate()
+                    .skip(skipped)
+                    .find_map(|(idx, (_, item))| {
+                        let found = matches!(item, Item::Folder { .. }) && item.name() == name;
+                        found.then_some(idx)
+                    })
+            };
+            match folder {
+                Some(idx) => {
+                    self.set_selected(idx);
+                    self.folder_path.push(FolderLevel { idx, selected: 0 });
+                }
+                None => {
+                    self.update_model();
+                    return 0;
+                }
+            }
+        }
+        self.update_model();
+
+        let entries = self.entries();
+        let idx = selection
+            .item
+            .as_ref()
+            .and_then(|name| entries.iter().position(|(_, item)| item.name() == name))
+            .unwrap_or(selection.index.min(entries.len().saturating_sub(1)));
+        self.set_selected(idx);
+        idx
+    }
+
+    /// Runs the item or performs its power action, the error tells why nothing is started.
+    /// Actions handled by the app itself are returned to the caller.
+    pub fn exec_item(&mut self, idx: usize) -> Result<Option<AppAction>, String> {
+        let Some((icon, item)) = self.entries().get(idx) else {
+            let reason = format!("Bad model index to run: {}", idx);
+            log::error!("{}", reason);
+            return Err(reason);
+        };
+        if !icon.enabled {
+            return Err(format!("`{}` is not available", item.name()));
+        }
+
+        match item.clone() {
+            Item::App { name, action } => {
+                log::info!("Run {:?} action of `{}`", action, name);
+                Ok(Some(action))
+            }
+            Item::Folder { .. } => Ok(Some(AppAction::OpenFolder(idx))),
+            Item::Power { name, action } => {
+                self.check_idle()?;
+                self.run_power_action(&name, action);
+                Ok(None)
+            }
+            Item::Exec(exec) => {
+                self.check_idle()?;
+                // The kiosk item started manually is supervised again
+                if let Some(supervisor) = &mut self.supervisor {
+                    if supervisor.item() == exec.name {
+                        supervisor.resume();
+                    }
+                }
+                self.start_item(*exec, idx)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Only one item runs at once, hooks of the previous item have to finish too.
+    fn check_idle(&mut self) -> Result<(), String> {
+        if !self.check_if_child_is_running() {
+            return Ok(());
+        }
+
+        let reason = match (&self.pending, &self.child) {
+            (Some(pending), _) => {
+                format!("Pre-launch hooks of `{}` are running", pending.exec.name)
+            }
+            (None, Some(child)) => format!("`{}` is already running", child.exec.name),
+            (None, None) => "Post-exit hooks are running".to_string(),
+        };
+        log::warn!("{}", reason);
+        Err(reason)
+    }
+
+    /// The action is requested in background as logind may wait for authentication.
+    fn run_power_action(&mut self, name: &str, action: PowerAction) {
+        log::info!("Run {:?} action of `{}`", action, name);
+        let name = name.to_string();
+        let failures_tx = self.failures_tx.clone();
+        thread::spawn(move || {
+            if let Err(error) = Logind::connect().and_then(|logind| logind.run(action)) {
+                log::error!("{}", error);
+                _ = failures_tx.send(LaunchFailure {
+                    name,
+                    reason: error,
+                    output: Vec::new(),
+                });
+            }
+        });
+    }
+
+    /// Starts the kiosk item when it's time to (re)start it.
+    pub fn poll_supervisor(&mut self) {
         if self.check_if_child_is_running() {
-            log::warn!("Try to run more than one application at once");
             return;
         }
 
-        if let Some(exec) = self.items.get(idx) {
-            let child = match Command::new(&exec.program).args(&exec.args).spawn() {
-                Ok(child) => Some(child),
+        let Some(supervisor) = &mut self.supervisor else {
+            return;
+        };
+        if !supervisor.poll_start(Instant::now()) {
+            return;
+        }
+
+        let name = supervisor.item();
+        match find_exec(&self.items, name).map(|(exec, idx)| (exec.clone(), idx)) {
+            Some((exec, idx)) => {
+                log::info!("Starting kiosk item `{}`", name);
+                // A failure to spawn is reported to the user and handled by the supervisor
+                _ = self.start_item(exec, idx);
+            }
+            None => {
+                log::error!("Kiosk item `{}` is not found", name);
+                self.supervisor = None;
+            }
+        }
+    }
+
+    /// Spawns the item or starts its pre-launch hooks, the error is a failure to spawn it.
+    fn start_item(&mut self, exec: Executable, idx: usize) -> Result<(), String> {
+        if exec.pre_exec.is_empty() {
+            return self.spawn_item(exec, idx);
+        }
+
+        let hooks = exec.pre_exec.clone();
+        let vars = hook_vars(&exec, idx, None);
+        self.pending = Some(PendingLaunch {
+            hooks: spawn_hooks(hooks, vars, self.hook_timeout),
+            exec,
+            idx,
+        });
+        Ok(())
+    }
+
+    fn spawn_item(&mut self, exec: Executable, idx: usize) -> Result<(), String> {
+        let child = exec.command().and_then(|mut command| {
+            // Output that isn't redirected by the config is logged
+            if exec.stdout.is_none() {
+                command.stdout(Stdio::piped());
+            }
+            if exec.stderr.is_none() {
+                command.stderr(Stdio::piped());
+            }
+            // The item gets its own process group to be able to stop all its processes at once
+            command.process_group(0);
+            command.spawn()
+        });
+
+        match child {
+            Ok(mut process) => {
+                let piped = exec.stdout.is_none() || exec.stderr.is_none();
+                let child_log = piped.then(|| ChildLog::start(&exec, &mut process));
+                self.state
+                    .borrow_mut()
+                    .record_launch(&exec.name, SystemTime::now());
+                self.child = Some(RunningChild {
+                    exec,
+                    idx,
+                    processes: ItemProcesses::new(process.id()),
+                    process,
+                    started_at: Some(SystemTime::now()),
+                    log: child_log,
+                    status: None,
+                    terminating_since: None,
+                    killed: false,
+                });
+                Ok(())
+            }
+            Err(error) => {
+                let reason = describe_spawn_error(&exec.program, &error);
+                log::error!("{}", reason);
+                self.report_failure(&exec.name, reason.clone());
+                self.start_post_hooks(&exec, idx, None);
+                self.on_item_exit(&exec.name, None);
+                Err(reason)
+            }
+        }
+    }
+
+    /// Post-exit hooks also run when the item fails to start after successful pre-launch hooks,
+    /// so they can restore changes made by pre-launch hooks.
+    fn start_post_hooks(&mut self, exec: &Executable, idx: usize, status: Option<ExitStatus>) {
+        if !exec.post_exec.is_empty() {
+            let vars = hook_vars(exec, idx, status);
+            let hooks = spawn_hooks(exec.post_exec.clone(), vars, self.hook_timeout);
+            self.post_hooks = Some(hooks);
+        }
+    }
+
+    /// Returns `true` while hooks are running.
+    fn poll_hooks(&mut self) -> bool {
+        if let Some(post_hooks) = &self.post_hooks {
+            if !post_hooks.is_finished() {
+                return true;
+            }
+            if let Some(Err(error)) = self.post_hooks.take().map(join_hooks) {
+                log::error!("Post-exit hook failed: {}", error);
+            }
+        }
+
+        let Some(pending) = &self.pending else {
+            return false;
+        };
+        if !pending.hooks.is_finished() {
+            return true;
+        }
+
+        if let Some(pending) = self.pending.take() {
+            match join_hooks(pending.hooks) {
+                Ok(()) => _ = self.spawn_item(pending.exec, pending.idx),
                 Err(error) => {
-                    log::error!(
-                        "Failed to execute the command `{}`: {}",
-                        exec.program,
-                        error
-                    );
-                    None
+                    let reason = format!("Pre-launch hook failed: {}", error);
+                    log::error!("{}", reason);
+                    self.report_failure(&pending.exec.name, reason);
+                    self.on_item_exit(&pending.exec.name, None);
                 }
-            };
+            }
+        }
+
+        // Post-exit hooks may be started if the item fails to start
+        self.post_hooks.is_some()
+    }
+
+    /// Asks all processes of the running item to terminate.
+    /// They are killed if they are still running after the kill timeout.
+    pub fn force_quit(&mut self) {
+        let Some(child) = &mut self.child else {
+            return;
+        };
+        if child.terminating_since.is_some() {
+            return;
+        }
+
+        log::info!("Force quit `{}`", child.exec.name);
+        child.processes.signal(Signal::SIGTERM);
+        child.terminating_since = Some(Instant::now());
+        // The play time doesn't include the time the item takes to quit
+        child.end_session(&mut self.sessions);
 
-            self.child_process = child;
-        } else {
-            log::error!("Bad model index to run: {}", idx);
+        // The user wants to get back to the launcher, so the kiosk item isn't restarted
+        if let Some(supervisor) = &mut self.supervisor {
+            supervisor.stop();
         }
     }
 
     pub fn check_if_child_is_running(&mut self) -> bool {
-        if let Some(child) = &mut self.child_process {
-            if let Ok(exit_status) = child.try_wait() {
-                if exit_status.is_none() {
+        // Hook processes are not reaped here, so they are waited for by their threads
+        if self.poll_hooks() {
+            return true;
+        }
+
+        let Some(child) = &mut self.child else {
+            // Processes are only scanned when there is something to reap
+            if process_tree::has_exited_children() {
+                process_tree::reap_adopted(&process_tree::processes(), None);
+            }
+            return false;
+        };
+
+        if let Some(terminating_since) = child.terminating_since {
+            if !child.killed && terminating_since.elapsed() >= self.kill_timeout {
+                log::warn!("`{}` doesn't quit, killing it", child.exec.name);
+                child.processes.signal(Signal::SIGKILL);
+                child.killed = true;
+            }
+        }
+
+        if child.status.is_none() {
+            match child.process.try_wait() {
+                Ok(None) => {
+                    let processes = process_tree::processes();
+                    process_tree::reap_adopted(&processes, Some(child.process.id()));
+                    // Tracked while the parents are alive, so they are known once adopted
+                    child.processes.update(&processes);
                     return true;
                 }
+                Ok(Some(status)) => {
+                    log::info!("Child process `{}` exited: {}", child.exec.name, status);
+                    child.status = Some(status);
+                }
+                Err(error) => {
+                    log::error!("Failed to check child process state: {}", error);
+                    child.end_session(&mut self.sessions);
+                    self.child = None;
+                    return false;
+                }
+            }
+        }
+
+        // Processes left by the item are adopted by the launcher, see `become_subreaper`
+        let processes = process_tree::processes();
+        process_tree::reap_adopted(&processes, None);
+        let descendants_running = child.processes.update(&processes);
+        if descendants_running && !child.exec.detach {
+            return true;
+        }
+
+        if let Some(mut child) = self.child.take() {
+            // The session lasts until all processes of the item exit
+            child.end_session(&mut self.sessions);
+
+            if let Some(status) = child.status {
+                self.start_post_hooks(&child.exec, child.idx, Some(status));
+                self.on_item_exit(&child.exec.name, Some(status));
+                self.report_exit(child, status);
+            }
+        }
+
+        self.post_hooks.is_some()
+    }
+
+    /// Records the session of the running item and waits until sessions are written.
+    /// It's called before gpcl quits or restarts.
+    pub fn finish_sessions(&mut self) {
+        if let Some(child) = &mut self.child {
+            child.end_session(&mut self.sessions);
+        }
+        self.sessions.finish();
+    }
+
+    /// Returns the next failure of launched applications to show.
+    pub fn poll_failure(&self) -> Option<LaunchFailure> {
+        self.failures_rx.try_recv().ok()
+    }
+
+    /// Schedules a restart of the kiosk item, `None` status means that it failed to start.
+    fn on_item_exit(&mut self, name: &str, status: Option<ExitStatus>) {
+        let Some(supervisor) = &mut self.supervisor else {
+            return;
+        };
+        if supervisor.item() != name {
+            return;
+        }
+
+        match supervisor.on_exit(status, Instant::now()) {
+            ExitAction::Restart(delay) => log::info!("Restarting `{}` in {:?}", name, delay),
+            ExitAction::Stop => {}
+            ExitAction::GiveUp => {
+                let reason = "Restarted too many times, giving up".to_string();
+                log::error!("`{}`: {}", name, reason);
+                self.report_failure(name, reason);
+            }
+        }
+    }
+
+    fn report_failure(&self, name: &str, reason: String) {
+        _ = self.failures_tx.send(LaunchFailure {
+            name: name.to_string(),
+            reason,
+            output: Vec::new(),
+        });
+    }
+
+    fn report_exit(&self, child: RunningChild, status: ExitStatus) {
+        // Exit of a force quit item is expected, so it isn't reported as a failure
+        let reason = match child.terminating_since {
+            Some(_) => None,
+            None => describe_exit_status(status),
+        };
+        let failures_tx = self.failures_tx.clone();
+        let name = child.exec.name;
+
+        let report = move |output| {
+            if let Some(reason) = reason {
+                _ = failures_tx.send(LaunchFailure {
+                    name,
+                    reason,
+                    output,
+                });
+            }
+        };
+
+        match child.log {
+            Some(child_log) => child_log.finish(status, report),
+            None => report(Vec::new()),
+        }
+    }
+}
+
+/// Grays out power actions that are not allowed, they stay enabled while availability is unknown.
+/// Returns `true` if there are power actions.
+fn update_availability(
+    entries: &mut [(AppIconModel, Item)],
+    power_actions: Option<&HashMap<PowerAction, bool>>,
+) -> bool {
+    let mut has_power_actions = false;
+    for (icon, item) in entries {
+        match item {
+            Item::Power { action, .. } => {
+                has_power_actions = true;
+                if let Some(power_actions) = power_actions {
+                    icon.enabled = power_actions.get(action).copied().unwrap_or(false);
+                }
+            }
+            Item::Folder { entries, .. } => {
+                has_power_actions |= update_availability(entries, power_actions)
             }
+            _ => {}
+        }
+    }
+    has_power_actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str) -> (AppIconModel, Item) {
+        let name = name.to_string();
+        let action = AppAction::Quit;
+        (AppIconModel::default(), Item::App { name, action })
+    }
+
+    fn folder(name: &str, entries: Vec<(AppIconModel, Item)>) -> (AppIconModel, Item) {
+        let name = name.to_string();
+        (AppIconModel::default(), Item::Folder { name, entries })
+    }
+
+    fn launcher() -> Launcher {
+        let mut launcher = Launcher::new(Rc::default());
+        launcher.items = vec![
+            app("Kodi"),
+            folder(
+                "Games",
+                vec![
+                    app("Doom"),
+                    app("Quake"),
+                    folder("Retro", vec![app("Tetris")]),
+                ],
+            ),
+        ];
+        launcher
+    }
+
+    #[test]
+    fn restores_selection_of_reopened_folders() {
+        let mut launcher = launcher();
+
+        assert_eq!(launcher.open_folder(1), 0);
+        launcher.set_selected(2);
+        assert_eq!(launcher.open_folder(2), 0);
+        assert_eq!(launcher.breadcrumbs(), ["Games", "Retro"]);
+
+        assert_eq!(launcher.close_folder(), Some(2));
+        assert_eq!(launcher.close_folder(), Some(1));
+        assert_eq!(launcher.close_folder(), None);
+        assert!(launcher.breadcrumbs().is_empty());
+
+        assert_eq!(launcher.open_folder(1), 2);
+        assert_eq!(launcher.open_folder(2), 0);
+        assert_eq!(launcher.breadcrumbs(), ["Games", "Retro"]);
+    }
+
+    #[test]
+    fn saves_selection_of_the_same_index_in_another_folder() {
+        let mut launcher = launcher();
+
+        launcher.select(0);
+        let idx = launcher.open_folder(1);
+        launcher.select(idx);
+
+        let expected = Selection {
+            folders: vec!["Games".into()],
+            item: Some("Doom".into()),
+            index: 0,
+            recent: false,
+        };
+        assert_eq!(launcher.state.borrow().selection(), &expected);
+    }
+
+    fn selection(folders: &[&str], item: &str, index: usize) -> Selection {
+        Selection {
+            folders: folders.iter().map(|name| name.to_string()).collect(),
+            item: Some(item.to_string()),
+            index,
+            recent: false,
         }
+    }
+
+    #[test]
+    fn restores_selection_in_folders() {
+        let mut launcher = launcher();
+
+        let idx = launcher.restore_selection(&selection(&["Games", "Retro"], "Tetris", 5));
+        assert_eq!(idx, 0);
+        assert_eq!(launcher.breadcrumbs(), ["Games", "Retro"]);
+
+        // The item is found by name if it's moved
+        let idx = launcher.restore_selection(&selection(&["Games"], "Quake", 0));
+        assert_eq!(idx, 1);
+        assert_eq!(launcher.breadcrumbs(), ["Games"]);
+
+        // Going back selects the folders of the restored selection
+        assert_eq!(launcher.close_folder(), Some(1));
+        assert!(launcher.breadcrumbs().is_empty());
+    }
+
+    #[test]
+    fn replaces_removed_item_by_its_neighbour() {
+        let mut launcher = launcher();
+
+        let idx = launcher.restore_selection(&selection(&["Games"], "Heretic", 1));
+        assert_eq!(idx, 1);
+        assert_eq!(launcher.breadcrumbs(), ["Games"]);
+
+        // The index is clamped if the removed item was the last one
+        let idx = launcher.restore_selection(&selection(&["Games"], "Heretic", 7));
+        assert_eq!(idx, 2);
+
+        let idx = launcher.restore_selection(&Selection::default());
+        assert_eq!(idx, 0);
+        assert!(launcher.breadcrumbs().is_empty());
+    }
+
+    #[test]
+    fn selects_first_item_of_parent_if_folder_is_removed() {
+        let mut launcher = launcher();
+
+        let idx = launcher.restore_selection(&selection(&["Games", "Arcade"], "Pong", 3));
+        assert_eq!(idx, 0);
+        assert_eq!(launcher.breadcrumbs(), ["Games"]);
+
+        // A folder isn't matched by an item with the same name
+        let idx = launcher.restore_selection(&selection(&["Kodi"], "Doom", 0));
+        assert_eq!(idx, 0);
+        assert!(launcher.breadcrumbs().is_empty());
+    }
+
+    #[test]
+    fn tells_recent_folder_from_configured_one() {
+        let mut launcher = launcher();
+        launcher.items.push(folder("Recent", vec![app("Quake")]));
+        launcher.recent_items = 5;
+        launcher.recent_icon = Some(AppIconModel::default());
+        launcher
+            .state
+            .borrow_mut()
+            .record_launch("Doom", SystemTime::now());
+        launcher.apply_order();
+
+        launcher.open_folder(3);
+        let configured = launcher.selection(0);
+        assert!(!configured.recent);
+        launcher.close_all_folders();
+        launcher.open_folder(0);
+        let recent = launcher.selection(0);
+        assert!(recent.recent);
+        assert_eq!(recent.folders, configured.folders);
+
+        assert_eq!(launcher.restore_selection(&configured), 0);
+        assert_eq!(launcher.entries()[0].1.name(), "Quake");
+        assert_eq!(launcher.restore_selection(&recent), 0);
+        assert_eq!(launcher.entries()[0].1.name(), "Doom");
+
+        // The configured folder is restored if the "Recent" folder is no longer shown
+        launcher.recent_icon = None;
+        launcher.apply_order();
+        assert_eq!(launcher.restore_selection(&configured), 0);
+        assert_eq!(launcher.breadcrumbs(), ["Recent"]);
+        assert_eq!(launcher.restore_selection(&recent), 0);
+        assert!(launcher.breadcrumbs().is_empty());
+    }
+
+    #[test]
+    fn forgets_selection_when_items_are_reordered() {
+        let mut launcher = launcher();
+
+        launcher.open_folder(1);
+        launcher.set_selected(1);
+        launcher.close_folder();
+
+        // Not a folder
+        assert_eq!(launcher.open_folder(0), 0);
+        assert!(launcher.breadcrumbs().is_empty());
+
+        assert_eq!(launcher.open_folder(1), 1);
+        launcher.close_folder();
 
-        false
+        launcher.close_all_folders();
+        assert_eq!(launcher.open_folder(1), 0);
     }
 }
@@ -1,41 +1,388 @@
-use crate::config::AppIconConfig;
+use crate::config::{
+    AppIconConfig, ExecConfig, ItemAction, ItemKind, LaunchConfig, StdioConfig, WrapperConfig,
+};
+use crate::expand::expand_path;
+use crate::icon_theme::IconLookup;
+use crate::launcher::exec_line::split_words;
+use crate::logind::PowerAction;
 use crate::AppIconModel;
 
 use slint::Image;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
 
+const SHELL: &str = "/bin/sh";
+
+/// Action handled by the app itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AppAction {
+    Reload,
+    Quit,
+    Settings,
+    Stats,
+    RestartGpcl,
+    /// Opens the folder with the given index in the current list
+    OpenFolder(usize),
+}
+
+/// What happens when an item is activated.
+#[derive(Clone)]
+pub enum Item {
+    Exec(Box<Executable>),
+    Power {
+        name: String,
+        action: PowerAction,
+    },
+    App {
+        name: String,
+        action: AppAction,
+    },
+    Folder {
+        name: String,
+        entries: Vec<(AppIconModel, Item)>,
+    },
+}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Exec(exec) => &exec.name,
+            Self::Power { name, .. } | Self::App { name, .. } | Self::Folder { name, .. } => name,
+        }
+    }
+}
+
+/// Finds the item that is not a folder by name, including items inside folders.
+pub fn find_item<'a>(
+    entries: &'a [(AppIconModel, Item)],
+    name: &str,
+) -> Option<&'a (AppIconModel, Item)> {
+    entries.iter().find_map(|entry| match &entry.1 {
+        Item::Folder { entries, .. } => find_item(entries, name),
+        item if item.name() == name => Some(entry),
+        _ => None,
+    })
+}
+
+/// Finds the item by name, including items inside folders.
+/// Returns indices of the containing folders followed by the index of the item.
+pub fn find_item_path(entries: &[(AppIconModel, Item)], name: &str) -> Option<Vec<usize>> {
+    entries
+        .iter()
+        .enumerate()
+        .find_map(|(idx, (_, item))| match item {
+            Item::Folder { entries, .. } => find_item_path(entries, name).map(|mut path| {
+                path.insert(0, idx);
+                path
+            }),
+            item if item.name() == name => Some(vec![idx]),
+            _ => None,
+        })
+}
+
+/// Finds the executable item by name, including items inside folders.
+/// Returns it with its index in the containing list.
+pub fn find_exec<'a>(
+    entries: &'a [(AppIconModel, Item)],
+    name: &str,
+) -> Option<(&'a Executable, usize)> {
+    entries
+        .iter()
+        .enumerate()
+        .find_map(|(idx, (_, item))| match item {
+            Item::Exec(exec) if exec.name == name => Some((exec.as_ref(), idx)),
+            Item::Folder { entries, .. } => find_exec(entries, name),
+            _ => None,
+        })
+}
+
+#[derive(Clone, Default)]
 pub struct Executable {
+    pub name: String,
     pub program: String,
     pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub env_remove: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub stdin: Option<StdioConfig>,
+    pub stdout: Option<StdioConfig>,
+    pub stderr: Option<StdioConfig>,
+    pub detach: bool,
+    pub pre_exec: Vec<Executable>,
+    pub post_exec: Vec<Executable>,
 }
 
 impl Executable {
-    fn new(path: &String) -> Self {
-        let mut tokens = path.split_whitespace();
-
-        let program = match tokens.next() {
-            Some(program) => program.into(),
-            None => {
-                log::error!("Failed to parse program name from: {}", path);
-                String::default()
-            }
+    pub fn new(exec: &ExecConfig, shell: bool) -> Result<Self, String> {
+        let mut tokens = match exec {
+            ExecConfig::Line(line) if shell => vec![line.clone()],
+            ExecConfig::Args(args) if shell => args.clone(),
+            exec => split_exec(exec)?,
+        };
+
+        if shell {
+            tokens.insert(0, "-c".into());
+            return Ok(Self {
+                program: SHELL.into(),
+                args: tokens,
+                ..Default::default()
+            });
+        }
+
+        if tokens.is_empty() {
+            return Err("no program name".into());
+        }
+
+        let program = tokens.remove(0);
+        Ok(Self {
+            program,
+            args: tokens,
+            ..Default::default()
+        })
+    }
+
+    /// Puts prefixes of the wrappers in front of the command line in the given order.
+    pub fn wrap<'a>(
+        mut self,
+        names: impl IntoIterator<Item = &'a String>,
+        wrappers: &BTreeMap<String, WrapperConfig>,
+    ) -> Result<Self, String> {
+        let mut prefix = Vec::new();
+        for name in names {
+            let wrapper = wrappers
+                .get(name)
+                .ok_or_else(|| format!("unknown wrapper `{}`", name))?;
+            let tokens = split_exec(&wrapper.prefix)
+                .map_err(|error| format!("wrapper `{}`: {}", name, error))?;
+            prefix.extend(tokens);
+        }
+
+        if prefix.is_empty() {
+            return Ok(self);
+        }
+
+        let program = std::mem::replace(&mut self.program, prefix.remove(0));
+        prefix.push(program);
+        prefix.append(&mut self.args);
+        self.args = prefix;
+        Ok(self)
+    }
+
+    /// Applies the global launch settings and the item settings on top of them.
+    fn with_launch_options(mut self, config: &AppIconConfig, launch: &LaunchConfig) -> Self {
+        self.name = config.name.clone();
+        // Items may remove variables set for every item
+        self.env = launch.env.clone();
+        self.env.retain(|name, _| !config.env_remove.contains(name));
+        self.env.extend(config.env.clone());
+        self.env_remove = config.env_remove.clone();
+        self.cwd = config.cwd.as_ref().map(PathBuf::from);
+        self.stdin = config.stdin.clone();
+        self.stdout = config.stdout.clone();
+        self.stderr = config.stderr.clone();
+        self.detach = config.detach;
+
+        // Global hooks wrap the item hooks
+        let parse_hook = |exec| {
+            Executable::new(exec, false).unwrap_or_else(|error| {
+                log::error!("Failed to parse hook of `{}`: {}", config.name, error);
+                Executable::default()
+            })
         };
+        self.pre_exec = launch
+            .pre_exec
+            .iter()
+            .chain(&config.pre_exec)
+            .map(parse_hook)
+            .collect();
+        self.post_exec = config
+            .post_exec
+            .iter()
+            .chain(&launch.post_exec)
+            .map(parse_hook)
+            .collect();
+        self
+    }
+
+    pub fn command_line(&self) -> Vec<&str> {
+        let args = self.args.iter().map(String::as_str);
+        [self.program.as_str()].into_iter().chain(args).collect()
+    }
+
+    pub fn command(&self) -> io::Result<Command> {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+
+        for name in &self.env_remove {
+            command.env_remove(name);
+        }
+        command.envs(&self.env);
+
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+
+        if let Some(stdin) = &self.stdin {
+            command.stdin(open_stdio(stdin, false)?);
+        }
+        if let Some(stdout) = &self.stdout {
+            command.stdout(open_stdio(stdout, true)?);
+        }
+        if let Some(stderr) = &self.stderr {
+            command.stderr(open_stdio(stderr, true)?);
+        }
+
+        Ok(command)
+    }
+}
+
+/// Splits the exec line into words or expands variables in separate arguments.
+fn split_exec(exec: &ExecConfig) -> Result<Vec<String>, String> {
+    match exec {
+        ExecConfig::Line(line) => split_words(line).map_err(|error| error.to_string()),
+        ExecConfig::Args(args) => args
+            .iter()
+            .map(|arg| expand_path(arg))
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.to_string()),
+    }
+}
 
-        let args = tokens.map(|str| str.to_string()).collect();
+/// Returns names of the default wrappers unless the item opts out of them,
+/// followed by names of the item wrappers.
+pub fn item_wrappers<'a>(
+    config: &'a AppIconConfig,
+    launch: &'a LaunchConfig,
+) -> impl Iterator<Item = &'a String> {
+    let defaults = match config.default_wrappers {
+        Some(false) => None,
+        _ => launch.wrappers.as_ref(),
+    };
+    defaults.into_iter().flatten().chain(&config.wrappers)
+}
 
-        Self { program, args }
+fn open_stdio(config: &StdioConfig, write: bool) -> io::Result<Stdio> {
+    match config {
+        StdioConfig::Null => Ok(Stdio::null()),
+        StdioConfig::Inherit => Ok(Stdio::inherit()),
+        StdioConfig::File(path) if write => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Ok(file.into())
+        }
+        StdioConfig::File(path) => Ok(File::open(path)?.into()),
     }
 }
 
-pub fn config_entry_into_item(config: &AppIconConfig) -> (AppIconModel, Executable) {
-    let image = Image::load_from_path(Path::new(config.icon.as_str())).unwrap_or_else(|_| {
-        log::error!("Filed to load image: `{}`", config.icon);
-        Image::default()
-    });
+pub fn config_entry_into_item(
+    config: &AppIconConfig,
+    launch: &LaunchConfig,
+    wrappers: &BTreeMap<String, WrapperConfig>,
+    icons: &mut IconLookup,
+) -> (AppIconModel, Item) {
+    let image = icons
+        .find_icon(&config.icon)
+        .and_then(|path| Image::load_from_path(&path).ok())
+        .unwrap_or_else(|| {
+            log::error!("Filed to load image: `{}`", config.icon);
+            Image::default()
+        });
     let name = (&config.name).into();
-    let model = AppIconModel { image, name };
+    let model = AppIconModel {
+        image,
+        name,
+        confirm: config.confirm,
+        confirm_message: config.confirm_message.as_deref().unwrap_or_default().into(),
+        enabled: true,
+    };
+
+    let name = config.name.clone();
+    if config.kind == ItemKind::Folder {
+        let entries = config
+            .items
+            .iter()
+            .map(|item| config_entry_into_item(item, launch, wrappers, icons))
+            .collect();
+        return (model, Item::Folder { name, entries });
+    }
+
+    let item = match config.action {
+        None => {
+            let executable = Executable::new(&config.exec, config.shell)
+                .and_then(|exec| exec.wrap(item_wrappers(config, launch), wrappers))
+                .unwrap_or_else(|error| {
+                    log::error!("Failed to parse exec of `{}`: {}", config.name, error);
+                    Executable::default()
+                })
+                .with_launch_options(config, launch);
+            Item::Exec(Box::new(executable))
+        }
+        Some(ItemAction::PowerOff) => Item::Power {
+            name,
+            action: PowerAction::PowerOff,
+        },
+        Some(ItemAction::Reboot) => Item::Power {
+            name,
+            action: PowerAction::Reboot,
+        },
+        Some(ItemAction::Suspend) => Item::Power {
+            name,
+            action: PowerAction::Suspend,
+        },
+        Some(ItemAction::Hibernate) => Item::Power {
+            name,
+            action: PowerAction::Hibernate,
+        },
+        Some(ItemAction::Reload) => Item::App {
+            name,
+            action: AppAction::Reload,
+        },
+        Some(ItemAction::Quit) => Item::App {
+            name,
+            action: AppAction::Quit,
+        },
+        Some(ItemAction::Settings) => Item::App {
+            name,
+            action: AppAction::Settings,
+        },
+        Some(ItemAction::Stats) => Item::App {
+            name,
+            action: AppAction::Stats,
+        },
+        Some(ItemAction::RestartGpcl) => Item::App {
+            name,
+            action: AppAction::RestartGpcl,
+        },
+    };
 
-    let executable = Executable::new(&config.exec);
+    (model, item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_global_env_of_item() {
+        let launch = LaunchConfig {
+            env: BTreeMap::from([
+                ("LD_PRELOAD".to_string(), "libhook.so".to_string()),
+                ("SDL_VIDEODRIVER".to_string(), "wayland".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let config = AppIconConfig {
+            name: "Emulator".to_string(),
+            env: BTreeMap::from([("SDL_VIDEODRIVER".to_string(), "x11".to_string())]),
+            env_remove: vec!["LD_PRELOAD".to_string()],
+            ..Default::default()
+        };
 
-    (model, executable)
+        let exec = Executable::default().with_launch_options(&config, &launch);
+        let expected = BTreeMap::from([("SDL_VIDEODRIVER".to_string(), "x11".to_string())]);
+        assert_eq!(exec.env, expected);
+        assert_eq!(exec.env_remove, ["LD_PRELOAD"]);
+    }
 }
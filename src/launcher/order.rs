@@ -0,0 +1,130 @@
+use crate::config::SortOrder;
+use crate::launcher::model::Item;
+use crate::state::LaunchStats;
+use crate::AppIconModel;
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Sorts the items, items that are equal in the given order keep the config order.
+pub fn sort_items<T>(
+    items: &mut [T],
+    order: SortOrder,
+    name: impl Fn(&T) -> &str,
+    stats: impl Fn(&T) -> LaunchStats,
+) {
+    match order {
+        SortOrder::Config => {}
+        SortOrder::Alphabetical => items.sort_by_cached_key(|item| name(item).to_lowercase()),
+        SortOrder::Recent => items.sort_by_cached_key(|item| Reverse(stats(item).last_launch)),
+        SortOrder::MostUsed => items.sort_by_cached_key(|item| {
+            let stats = stats(item);
+            Reverse((stats.count, stats.last_launch))
+        }),
+    }
+}
+
+/// Names of the launched items, the most recent first.
+pub fn recent_names(launches: &BTreeMap<String, LaunchStats>) -> Vec<&str> {
+    let mut names: Vec<_> = launches.iter().collect();
+    names.sort_by_key(|(_, stats)| Reverse(stats.last_launch));
+    names.into_iter().map(|(name, _)| name.as_str()).collect()
+}
+
+/// Sorts items of the list and its folders.
+pub fn sort_entries(
+    entries: &mut [(AppIconModel, Item)],
+    order: SortOrder,
+    launches: &BTreeMap<String, LaunchStats>,
+) {
+    if order == SortOrder::Config {
+        return;
+    }
+
+    for (_, item) in entries.iter_mut() {
+        if let Item::Folder { entries, .. } = item {
+            sort_entries(entries, order, launches);
+        }
+    }
+
+    sort_items(
+        entries,
+        order,
+        |(_, item)| item.name(),
+        |(_, item)| item_stats(item, launches),
+    );
+}
+
+/// Folders are ordered by the statistics of all their items.
+fn item_stats(item: &Item, launches: &BTreeMap<String, LaunchStats>) -> LaunchStats {
+    match item {
+        Item::Folder { entries, .. } => entries
+            .iter()
+            .map(|(_, item)| item_stats(item, launches))
+            .fold(LaunchStats::default(), LaunchStats::merge),
+        item => launches.get(item.name()).copied().unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(count: u32, last_launch: u64) -> LaunchStats {
+        LaunchStats { count, last_launch }
+    }
+
+    fn sorted(order: SortOrder) -> Vec<&'static str> {
+        let mut items = vec![
+            ("kodi", stats(3, 100)),
+            ("Steam", stats(1, 300)),
+            ("RetroArch", stats(3, 200)),
+            ("Firefox", LaunchStats::default()),
+            ("chromium", LaunchStats::default()),
+        ];
+        sort_items(&mut items, order, |item| item.0, |item| item.1);
+        items.into_iter().map(|item| item.0).collect()
+    }
+
+    #[test]
+    fn keeps_config_order() {
+        let expected = ["kodi", "Steam", "RetroArch", "Firefox", "chromium"];
+        assert_eq!(sorted(SortOrder::Config), expected);
+    }
+
+    #[test]
+    fn sorts_alphabetically_ignoring_case() {
+        let expected = ["chromium", "Firefox", "kodi", "RetroArch", "Steam"];
+        assert_eq!(sorted(SortOrder::Alphabetical), expected);
+    }
+
+    #[test]
+    fn sorts_recent_first_and_keeps_order_of_never_launched() {
+        let expected = ["Steam", "RetroArch", "kodi", "Firefox", "chromium"];
+        assert_eq!(sorted(SortOrder::Recent), expected);
+    }
+
+    #[test]
+    fn sorts_most_used_first_and_recent_on_ties() {
+        let expected = ["RetroArch", "kodi", "Steam", "Firefox", "chromium"];
+        assert_eq!(sorted(SortOrder::MostUsed), expected);
+    }
+
+    #[test]
+    fn lists_recent_names_first() {
+        let launches = BTreeMap::from([
+            ("Kodi".to_string(), stats(5, 100)),
+            ("Steam".to_string(), stats(1, 300)),
+            ("RetroArch".to_string(), stats(2, 200)),
+        ]);
+
+        assert_eq!(recent_names(&launches), ["Steam", "RetroArch", "Kodi"]);
+        assert!(recent_names(&BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn merges_stats() {
+        let merged = stats(2, 100).merge(stats(3, 50));
+        assert_eq!(merged, stats(5, 100));
+    }
+}
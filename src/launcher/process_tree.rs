@@ -0,0 +1,262 @@
+use nix::errno::Errno;
+use nix::sys::prctl;
+use nix::sys::signal::{kill, killpg, Signal};
+use nix::sys::wait::{waitid, waitpid, Id, WaitPidFlag, WaitStatus};
+use nix::unistd::Pid;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ProcessInfo {
+    pid: u32,
+    ppid: u32,
+    pgid: u32,
+    is_zombie: bool,
+}
+
+/// Makes the launcher adopt orphaned descendants of launched applications instead of init,
+/// so applications that fork and exit early (like game stores) can still be tracked.
+pub fn become_subreaper() {
+    if let Err(error) = prctl::set_child_subreaper(true) {
+        log::error!("Failed to become a child subreaper: {}", error);
+    }
+}
+
+/// Whether a child of the launcher has exited, it isn't reaped.
+pub fn has_exited_children() -> bool {
+    let flags = WaitPidFlag::WEXITED | WaitPidFlag::WNOHANG | WaitPidFlag::WNOWAIT;
+    !matches!(
+        waitid(Id::All, flags),
+        Ok(WaitStatus::StillAlive) | Err(Errno::ECHILD)
+    )
+}
+
+/// Reaps exited processes adopted by the launcher.
+/// The process with `except` pid is skipped as it is waited for separately.
+pub fn reap_adopted(processes: &[ProcessInfo], except: Option<u32>) {
+    let parent = std::process::id();
+    for process in processes {
+        if process.ppid == parent && process.is_zombie && Some(process.pid) != except {
+            let pid = Pid::from_raw(process.pid as i32);
+            if let Err(error) = waitpid(pid, Some(WaitPidFlag::WNOHANG)) {
+                log::error!("Failed to reap process {}: {}", pid, error);
+            }
+        }
+    }
+}
+
+/// Processes of a launched item: its process group, their descendants and processes
+/// adopted by the launcher while the item runs. Daemons left by earlier items are not tracked.
+pub struct ItemProcesses {
+    pgid: u32,
+    /// Descendants of the launcher that were running before the item started
+    foreign: HashSet<u32>,
+    tracked: HashSet<u32>,
+}
+
+impl ItemProcesses {
+    /// The launched process leads its own process group.
+    pub fn new(pid: u32) -> Self {
+        let mut foreign = descendants(&processes(), std::process::id());
+        foreign.remove(&pid);
+        Self {
+            pgid: pid,
+            foreign,
+            tracked: HashSet::from([pid]),
+        }
+    }
+
+    /// Returns `true` if processes of the item other than the launched one are running.
+    pub fn update(&mut self, processes: &[ProcessInfo]) -> bool {
+        self.track(processes, std::process::id())
+    }
+
+    /// Sends the signal to the process group of the item
+    /// and to its processes that have left the group.
+    pub fn signal(&mut self, signal: Signal) {
+        let processes = processes();
+        self.track(&processes, std::process::id());
+
+        let pgid = self.pgid;
+        match killpg(Pid::from_raw(pgid as i32), signal) {
+            Ok(()) | Err(Errno::ESRCH) => {}
+            Err(error) => log::error!("Failed to send {} to group {}: {}", signal, pgid, error),
+        }
+
+        for process in processes {
+            if process.is_zombie || process.pgid == pgid || !self.tracked.contains(&process.pid) {
+                continue;
+            }
+            match kill(Pid::from_raw(process.pid as i32), signal) {
+                Ok(()) | Err(Errno::ESRCH) => {}
+                Err(error) => {
+                    log::error!(
+                        "Failed to send {} to process {}: {}",
+                        signal,
+                        process.pid,
+                        error
+                    )
+                }
+            }
+        }
+    }
+
+    fn track(&mut self, processes: &[ProcessInfo], launcher: u32) -> bool {
+        loop {
+            let mut changed = false;
+            for process in processes {
+                if self.tracked.contains(&process.pid) {
+                    continue;
+                }
+                let adopted = process.ppid == launcher && !self.foreign.contains(&process.pid);
+                if process.pgid == self.pgid || self.tracked.contains(&process.ppid) || adopted {
+                    self.tracked.insert(process.pid);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // Pids of exited processes may be reused
+        let existing: HashSet<_> = processes.iter().map(|process| process.pid).collect();
+        self.tracked.retain(|pid| existing.contains(pid));
+
+        processes.iter().any(|process| {
+            !process.is_zombie && process.pid != self.pgid && self.tracked.contains(&process.pid)
+        })
+    }
+}
+
+/// Reads all processes from `/proc`, it's done once per poll as it's a scan of the whole system.
+pub fn processes() -> Vec<ProcessInfo> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+            parse_stat(pid, &stat)
+        })
+        .collect()
+}
+
+/// Pids of all descendants of the process.
+fn descendants(processes: &[ProcessInfo], pid: u32) -> HashSet<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for process in processes {
+        children.entry(process.ppid).or_default().push(process.pid);
+    }
+
+    let mut result = HashSet::new();
+    let mut queue = vec![pid];
+    while let Some(pid) = queue.pop() {
+        for &child in children.get(&pid).into_iter().flatten() {
+            if result.insert(child) {
+                queue.push(child);
+            }
+        }
+    }
+    result
+}
+
+/// Extracts the state, the parent pid and the process group from `/proc/<pid>/stat`.
+/// The command name may contain spaces and parentheses, so fields are read after the last `)`.
+fn parse_stat(pid: u32, stat: &str) -> Option<ProcessInfo> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace();
+    let state = fields.next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    let pgid = fields.next()?.parse().ok()?;
+    Some(ProcessInfo {
+        pid,
+        ppid,
+        pgid,
+        is_zombie: state == "Z",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAUNCHER: u32 = 10;
+
+    fn process(pid: u32, ppid: u32, pgid: u32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            pgid,
+            is_zombie: false,
+        }
+    }
+
+    fn new_item(processes: &[ProcessInfo], pid: u32) -> ItemProcesses {
+        let mut foreign = descendants(processes, LAUNCHER);
+        foreign.remove(&pid);
+        ItemProcesses {
+            pgid: pid,
+            foreign,
+            tracked: HashSet::from([pid]),
+        }
+    }
+
+    #[test]
+    fn parses_stat() {
+        let info = parse_stat(42, "42 (a) b)) S 10 42 42 0 -1").unwrap();
+        assert_eq!((info.ppid, info.pgid, info.is_zombie), (10, 42, false));
+        assert!(parse_stat(42, "42 (a) Z 10 42").unwrap().is_zombie);
+        assert!(parse_stat(42, "42 (a) S").is_none());
+    }
+
+    #[test]
+    fn finds_exited_children_without_reaping_them() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let started = std::time::Instant::now();
+        while !has_exited_children() {
+            assert!(started.elapsed().as_secs() < 5);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn ignores_daemons_of_earlier_items() {
+        // The daemon 20 and its child 21 are left by a detached item
+        let daemon = [process(20, LAUNCHER, 20), process(21, 20, 20)];
+        let mut processes = daemon.to_vec();
+        processes.push(process(30, LAUNCHER, 30));
+        let mut item = new_item(&processes, 30);
+
+        assert!(!item.track(&processes, LAUNCHER));
+        assert!(!item.track(&daemon, LAUNCHER));
+    }
+
+    #[test]
+    fn tracks_descendants_left_by_the_item() {
+        let mut item = new_item(&[process(30, LAUNCHER, 30)], 30);
+
+        // Child in the group, a process that left the group and its child
+        let processes = [
+            process(30, LAUNCHER, 30),
+            process(31, 30, 30),
+            process(32, 31, 32),
+            process(33, 32, 32),
+        ];
+        assert!(item.track(&processes, LAUNCHER));
+
+        // The launched process exits, the session leader is adopted by the launcher
+        let processes = [process(32, LAUNCHER, 32), process(33, 32, 32)];
+        assert!(item.track(&processes, LAUNCHER));
+
+        // A double-forked process adopted between polls
+        let mut item = new_item(&[process(30, LAUNCHER, 30)], 30);
+        assert!(item.track(&[process(34, LAUNCHER, 34)], LAUNCHER));
+
+        assert!(!item.track(&[], LAUNCHER));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::config::{Diagnostic, Diagnostics, KioskConfig, RestartPolicy};
+
+use std::collections::VecDeque;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+
+const DEFAULT_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_RESTARTS: u32 = 5;
+/// Restarts older than this are not counted for the backoff and the crash loop detection
+const RESTART_WINDOW: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartOptions {
+    pub policy: RestartPolicy,
+    pub delay: Duration,
+    pub max_restarts: u32,
+}
+
+impl Default for RestartOptions {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicy::default(),
+            delay: DEFAULT_RESTART_DELAY,
+            max_restarts: DEFAULT_MAX_RESTARTS,
+        }
+    }
+}
+
+impl RestartOptions {
+    pub fn from_config(config: &KioskConfig, diagnostics: &mut Diagnostics) -> Self {
+        let mut options = Self {
+            policy: config.restart.unwrap_or_default(),
+            ..Default::default()
+        };
+
+        if let Some(delay) = config.restart_delay {
+            match Duration::try_from_secs_f32(delay) {
+                Ok(delay) => options.delay = delay,
+                Err(_) => {
+                    let message = format!("Invalid kiosk restart delay `{}`", delay);
+                    diagnostics.push(Diagnostic::warning(message));
+                }
+            }
+        }
+
+        if let Some(max_restarts) = config.max_restarts {
+            options.max_restarts = max_restarts;
+        }
+
+        options
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExitAction {
+    Restart(Duration),
+    Stop,
+    /// The item is restarted too often, so it is likely to crash at startup
+    GiveUp,
+}
+
+#[derive(Debug, PartialEq)]
+enum State {
+    StartAt(Instant),
+    Running,
+    Stopped,
+}
+
+/// Starts the kiosk item and decides if it should be restarted when it exits.
+pub struct Supervisor {
+    item: String,
+    options: RestartOptions,
+    state: State,
+    restarts: VecDeque<Instant>,
+}
+
+impl Supervisor {
+    /// The item is started on the first poll.
+    pub fn new(item: String, options: RestartOptions, now: Instant) -> Self {
+        Self {
+            item,
+            options,
+            state: State::StartAt(now),
+            restarts: VecDeque::new(),
+        }
+    }
+
+    pub fn item(&self) -> &str {
+        &self.item
+    }
+
+    pub fn set_options(&mut self, options: RestartOptions) {
+        self.options = options;
+    }
+
+    /// Returns `true` once it's time to start the item.
+    pub fn poll_start(&mut self, now: Instant) -> bool {
+        match self.state {
+            State::StartAt(at) if now >= at => {
+                self.state = State::Running;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles the exit of the item, `None` status means that it failed to start.
+    pub fn on_exit(&mut self, status: Option<ExitStatus>, now: Instant) -> ExitAction {
+        if self.state != State::Running {
+            return ExitAction::Stop;
+        }
+
+        let success = status.is_some_and(|status| status.success());
+        let restart = match self.options.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !success,
+            RestartPolicy::Always => true,
+        };
+
+        if !restart {
+            self.state = State::Stopped;
+            return ExitAction::Stop;
+        }
+
+        while let Some(&restarted_at) = self.restarts.front() {
+            if now.duration_since(restarted_at) < RESTART_WINDOW {
+                break;
+            }
+            self.restarts.pop_front();
+        }
+
+        if self.restarts.len() >= self.options.max_restarts as usize {
+            self.state = State::Stopped;
+            return ExitAction::GiveUp;
+        }
+
+        // The delay is doubled for every recent restart
+        let factor = 1u32
+            .checked_shl(self.restarts.len() as u32)
+            .unwrap_or(u32::MAX);
+        let delay = self
+            .options
+            .delay
+            .saturating_mul(factor)
+            .min(MAX_RESTART_DELAY);
+
+        self.restarts.push_back(now);
+        self.state = State::StartAt(now + delay);
+        ExitAction::Restart(delay)
+    }
+
+    /// Stops restarting the item, e.g. when the user force quits it to reach the launcher.
+    pub fn stop(&mut self) {
+        self.state = State::Stopped;
+    }
+
+    /// Supervises the item started manually from the launcher again.
+    pub fn resume(&mut self) {
+        self.state = State::Running;
+        self.restarts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn run(script: &str) -> ExitStatus {
+        Command::new("sh").args(["-c", script]).status().unwrap()
+    }
+
+    fn supervisor(policy: RestartPolicy, now: Instant) -> Supervisor {
+        let options = RestartOptions {
+            policy,
+            delay: Duration::from_secs(1),
+            max_restarts: 3,
+        };
+        let mut supervisor = Supervisor::new("Kodi".into(), options, now);
+        assert!(supervisor.poll_start(now));
+        supervisor
+    }
+
+    #[test]
+    fn starts_item_once() {
+        let now = Instant::now();
+        let mut supervisor = Supervisor::new("Kodi".into(), RestartOptions::default(), now);
+
+        assert!(supervisor.poll_start(now));
+        assert!(!supervisor.poll_start(now));
+    }
+
+    #[test]
+    fn never_restarts_with_never_policy() {
+        let now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::Never, now);
+
+        assert_eq!(
+            supervisor.on_exit(Some(run("exit 1")), now),
+            ExitAction::Stop
+        );
+        assert!(!supervisor.poll_start(now + MAX_RESTART_DELAY));
+    }
+
+    #[test]
+    fn restarts_only_failures_with_on_failure_policy() {
+        let now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::OnFailure, now);
+
+        let delay = Duration::from_secs(1);
+        assert_eq!(
+            supervisor.on_exit(Some(run("exit 3")), now),
+            ExitAction::Restart(delay)
+        );
+        assert!(!supervisor.poll_start(now));
+        assert!(supervisor.poll_start(now + delay));
+
+        assert_eq!(supervisor.on_exit(Some(run("true")), now), ExitAction::Stop);
+    }
+
+    #[test]
+    fn treats_signals_and_start_errors_as_failures() {
+        let now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::OnFailure, now);
+
+        let action = supervisor.on_exit(Some(run("kill -TERM $$")), now);
+        assert!(matches!(action, ExitAction::Restart(_)));
+        assert!(supervisor.poll_start(now + MAX_RESTART_DELAY));
+
+        let action = supervisor.on_exit(None, now);
+        assert!(matches!(action, ExitAction::Restart(_)));
+    }
+
+    #[test]
+    fn restarts_successful_exits_with_always_policy() {
+        let now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::Always, now);
+
+        let action = supervisor.on_exit(Some(run("exit 0")), now);
+        assert_eq!(action, ExitAction::Restart(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn doubles_delay_and_gives_up_on_crash_loop() {
+        let mut now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::OnFailure, now);
+
+        for delay in [1, 2, 4] {
+            let action = supervisor.on_exit(Some(run("exit 1")), now);
+            assert_eq!(action, ExitAction::Restart(Duration::from_secs(delay)));
+
+            now += Duration::from_secs(delay);
+            assert!(supervisor.poll_start(now));
+        }
+
+        assert_eq!(
+            supervisor.on_exit(Some(run("exit 1")), now),
+            ExitAction::GiveUp
+        );
+        assert!(!supervisor.poll_start(now + MAX_RESTART_DELAY));
+    }
+
+    #[test]
+    fn forgets_old_restarts() {
+        let mut now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::Always, now);
+
+        for _ in 0..10 {
+            let action = supervisor.on_exit(Some(run("true")), now);
+            assert_eq!(action, ExitAction::Restart(Duration::from_secs(1)));
+
+            now += RESTART_WINDOW;
+            assert!(supervisor.poll_start(now));
+        }
+    }
+
+    #[test]
+    fn caps_delay() {
+        let now = Instant::now();
+        let options = RestartOptions {
+            policy: RestartPolicy::Always,
+            delay: Duration::from_secs(50),
+            max_restarts: 10,
+        };
+        let mut supervisor = Supervisor::new("Kodi".into(), options, now);
+
+        assert!(supervisor.poll_start(now));
+        supervisor.on_exit(Some(run("true")), now);
+        assert!(supervisor.poll_start(now + Duration::from_secs(50)));
+
+        let action = supervisor.on_exit(Some(run("true")), now);
+        assert_eq!(action, ExitAction::Restart(MAX_RESTART_DELAY));
+    }
+
+    #[test]
+    fn stops_on_force_quit_and_resumes_on_manual_start() {
+        let now = Instant::now();
+        let mut supervisor = supervisor(RestartPolicy::Always, now);
+
+        supervisor.stop();
+        assert_eq!(
+            supervisor.on_exit(Some(run("exit 1")), now),
+            ExitAction::Stop
+        );
+        assert!(!supervisor.poll_start(now + MAX_RESTART_DELAY));
+
+        supervisor.resume();
+        let action = supervisor.on_exit(Some(run("exit 1")), now);
+        assert_eq!(action, ExitAction::Restart(Duration::from_secs(1)));
+    }
+}
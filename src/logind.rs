@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use zbus::blocking::{Connection, Proxy};
+
+const DESTINATION: &str = "org.freedesktop.login1";
+const PATH: &str = "/org/freedesktop/login1";
+const INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerAction {
+    PowerOff,
+    Reboot,
+    Suspend,
+    Hibernate,
+}
+
+impl PowerAction {
+    pub const ALL: [PowerAction; 4] = [
+        PowerAction::PowerOff,
+        PowerAction::Reboot,
+        PowerAction::Suspend,
+        PowerAction::Hibernate,
+    ];
+}
+
+/// Client of the systemd-logind manager that performs power actions.
+pub struct Logind {
+    proxy: Proxy<'static>,
+}
+
+impl Logind {
+    pub fn connect() -> Result<Self, String> {
+        Connection::system()
+            .and_then(|connection| Self::new(&connection))
+            .map_err(|error| format!("Failed to connect to logind: {}", error))
+    }
+
+    fn new(connection: &Connection) -> zbus::Result<Self> {
+        let proxy = Proxy::new(connection, DESTINATION, PATH, INTERFACE)?;
+        Ok(Self { proxy })
+    }
+
+    /// Returns `true` if the action is supported and allowed, possibly after authentication.
+    pub fn is_available(&self, action: PowerAction) -> Result<bool, String> {
+        let (check_method, _) = power_methods(action);
+        let result: String = self
+            .proxy
+            .call(check_method, &())
+            .map_err(|error| format!("Failed to call {}: {}", check_method, error))?;
+
+        Ok(matches!(result.as_str(), "yes" | "challenge"))
+    }
+
+    /// Checks all actions, actions that can't be checked are unavailable.
+    pub fn query_availability() -> HashMap<PowerAction, bool> {
+        let logind = Self::connect();
+        PowerAction::ALL
+            .into_iter()
+            .map(|action| {
+                let result = logind
+                    .as_ref()
+                    .map_err(Clone::clone)
+                    .and_then(|logind| logind.is_available(action));
+                let is_available = result.unwrap_or_else(|error| {
+                    log::error!("{}", error);
+                    false
+                });
+                (action, is_available)
+            })
+            .collect()
+    }
+
+    pub fn run(&self, action: PowerAction) -> Result<(), String> {
+        let (_, method) = power_methods(action);
+        // Interactive mode lets polkit ask for authentication if it is required
+        self.proxy
+            .call::<_, _, ()>(method, &(true,))
+            .map_err(|error| format!("Failed to call {}: {}", method, error))
+    }
+}
+
+/// Returns names of the logind methods that check and perform the action.
+fn power_methods(action: PowerAction) -> (&'static str, &'static str) {
+    match action {
+        PowerAction::PowerOff => ("CanPowerOff", "PowerOff"),
+        PowerAction::Reboot => ("CanReboot", "Reboot"),
+        PowerAction::Suspend => ("CanSuspend", "Suspend"),
+        PowerAction::Hibernate => ("CanHibernate", "Hibernate"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use zbus::blocking::connection;
+
+    /// Private bus daemon that is stopped when dropped.
+    struct TestBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl TestBus {
+        /// Returns `None` if `dbus-daemon` isn't installed, so the tests are ignored by default.
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            let stdout = daemon.stdout.take()?;
+            BufReader::new(stdout).read_line(&mut address).ok()?;
+
+            Some(Self {
+                daemon,
+                address: address.trim().into(),
+            })
+        }
+
+        fn connect(&self) -> Connection {
+            connection::Builder::address(self.address.as_str())
+                .unwrap()
+                .build()
+                .unwrap()
+        }
+    }
+
+    impl Drop for TestBus {
+        fn drop(&mut self) {
+            _ = self.daemon.kill();
+            _ = self.daemon.wait();
+        }
+    }
+
+    struct MockManager {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.login1.Manager")]
+    impl MockManager {
+        fn can_power_off(&self) -> String {
+            "yes".into()
+        }
+
+        fn can_reboot(&self) -> String {
+            "challenge".into()
+        }
+
+        fn can_suspend(&self) -> String {
+            "no".into()
+        }
+
+        fn can_hibernate(&self) -> String {
+            "na".into()
+        }
+
+        fn power_off(&self, interactive: bool) {
+            let call = format!("PowerOff({})", interactive);
+            self.calls.lock().unwrap().push(call);
+        }
+
+        fn reboot(&self, _interactive: bool) -> zbus::fdo::Result<()> {
+            Err(zbus::fdo::Error::AccessDenied("Not allowed".into()))
+        }
+    }
+
+    fn start_mock_logind(bus: &TestBus) -> (Connection, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let manager = MockManager {
+            calls: calls.clone(),
+        };
+
+        let connection = connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name(DESTINATION)
+            .unwrap()
+            .serve_at(PATH, manager)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        (connection, calls)
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn checks_action_availability() {
+        let bus = TestBus::start().expect("dbus-daemon is not found");
+        let _service = start_mock_logind(&bus);
+        let logind = Logind::new(&bus.connect()).unwrap();
+
+        assert_eq!(logind.is_available(PowerAction::PowerOff), Ok(true));
+        assert_eq!(logind.is_available(PowerAction::Reboot), Ok(true));
+        assert_eq!(logind.is_available(PowerAction::Suspend), Ok(false));
+        assert_eq!(logind.is_available(PowerAction::Hibernate), Ok(false));
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn runs_actions() {
+        let bus = TestBus::start().expect("dbus-daemon is not found");
+        let (_service, calls) = start_mock_logind(&bus);
+        let logind = Logind::new(&bus.connect()).unwrap();
+
+        assert_eq!(logind.run(PowerAction::PowerOff), Ok(()));
+        assert_eq!(*calls.lock().unwrap(), ["PowerOff(true)"]);
+
+        let error = logind.run(PowerAction::Reboot).unwrap_err();
+        assert!(error.contains("Not allowed"), "{}", error);
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn reports_missing_service() {
+        let bus = TestBus::start().expect("dbus-daemon is not found");
+        let logind = Logind::new(&bus.connect()).unwrap();
+
+        assert!(logind.is_available(PowerAction::Suspend).is_err());
+    }
+}
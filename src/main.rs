@@ -1,20 +1,44 @@
+mod check;
+mod cli;
 mod clock;
 mod config;
+mod config_watcher;
+mod control;
+mod desktop_entries;
+mod expand;
 mod gamepad_manager;
+mod icon_theme;
+mod key_file;
 mod launcher;
+mod logind;
+mod play_time;
+mod program_path;
+mod state;
 mod winit;
 
-use config::{Config, LayoutConfig};
+use check::check_config;
+use cli::{Args, Command, WindowMode};
+use config::{
+    drop_in_dir, load_config, Config, Diagnostic, Diagnostics, LayoutConfig, LayoutMode, Severity,
+};
+use config_watcher::ConfigWatcher;
+use control::{ControlServer, GamepadInfo, LauncherState, Request, Response};
 use gamepad_manager::GamepadManager;
-use launcher::Launcher;
+use icon_theme::IconLookup;
+use launcher::{become_subreaper, AppAction, Launcher};
+use state::StateStore;
 use winit::WinitWindow;
 
 use crate::clock::ClockTracker;
 use crate::config::StyleConfig;
 use hex_color::HexColor;
-use slint::{Color, Timer, TimerMode};
+use slint::{Color, LogicalSize, Model, Timer, TimerMode, VecModel};
 use std::cell::RefCell;
-use std::fs;
+use std::env;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::rc::Rc;
 use std::time::Duration;
 
@@ -22,63 +46,215 @@ slint::include_modules!();
 
 pub const CONFIG_FILE_NAME: &str = "gpcl.toml";
 
-fn main() {
+fn main() -> ExitCode {
+    let args = match cli::parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    // Check reports problems to stdout, so logs are disabled by default to avoid duplicates.
+    // Stats and ctl output is meant for scripts, so it is kept clean as well.
+    let default_log_level = match args.command {
+        Command::Check | Command::Stats(_) | Command::Ctl(_) => log::LevelFilter::Off,
+        _ => log::LevelFilter::Debug,
+    };
     env_logger::builder()
-        .filter_level(log::LevelFilter::Debug)
+        .filter_level(args.log_level.unwrap_or(default_log_level))
         .init();
 
+    let config_path = args.config.clone().unwrap_or_else(default_config_path);
+
+    match args.command {
+        Command::Run => run(&args, config_path),
+        Command::Check => match check_config(&config_path) {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        },
+        Command::Stats(format) => match play_time::print_stats(format) {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        },
+        Command::Ctl(request) => match control::send_request(&request) {
+            Ok(response) => {
+                println!("{}", response);
+                match control::is_ok_response(&response) {
+                    true => ExitCode::SUCCESS,
+                    false => ExitCode::FAILURE,
+                }
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            ExitCode::SUCCESS
+        }
+        Command::Version => {
+            println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+fn run(args: &Args, config_path: PathBuf) -> ExitCode {
     let app = GpclApp::new().unwrap();
 
     let window = app.window();
-    window.set_fullscreen(true);
-    _ = app
-        .as_weak()
-        .upgrade_in_event_loop(move |app| app.window().hide_cursor());
+    match args.window_mode {
+        WindowMode::Fullscreen => {
+            window.set_fullscreen(true);
+            _ = app
+                .as_weak()
+                .upgrade_in_event_loop(move |app| app.window().hide_cursor());
+        }
+        WindowMode::Windowed(Some((width, height))) => {
+            window.set_size(LogicalSize::new(width, height));
+        }
+        WindowMode::Windowed(None) => {}
+    }
 
-    let mut launcher = Launcher::new();
-    load_and_apply_config(&app, &mut launcher);
+    let settings_info = format!(
+        "{} {}\nConfig: {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        config_path.display()
+    );
+    app.set_settings_info(settings_info.into());
 
-    let launcher = Rc::new(RefCell::new(launcher));
-    setup_config_reloading(&app, launcher.clone());
+    become_subreaper();
+    let state = Rc::new(RefCell::new(StateStore::open()));
+    let mut launcher = Launcher::new(state.clone());
+    let mut config_files = Vec::new();
+    _ = load_and_apply_config(&app, &mut launcher, &config_path, &mut config_files);
 
-    let _gp_poll_timer = setup_gamepad_manager(&app);
+    let launcher = Rc::new(RefCell::new(launcher));
+    let (_config_watch_timer, reload_config) = setup_config_reloading(
+        &app,
+        launcher.clone(),
+        state.clone(),
+        config_path,
+        &config_files,
+    );
+
+    let _gp_poll_timer = setup_gamepad_manager(&app, launcher.clone());
     let _clock_timer = setup_clock(&app);
-    let _launcher_timer = setup_launcher(&app, launcher);
+    let _control_timer = setup_control(&app, launcher.clone(), state.clone(), reload_config);
+    let _launcher_timer = setup_launcher(&app, launcher.clone(), state.clone());
 
     app.run().unwrap();
+    launcher.borrow_mut().finish_sessions();
+    state.borrow_mut().flush();
+    ExitCode::SUCCESS
 }
 
-fn load_config_file() -> Config {
+fn default_config_path() -> PathBuf {
     let xdg_dirs = xdg::BaseDirectories::new();
-    let config_path = xdg_dirs.get_config_file(CONFIG_FILE_NAME).unwrap();
+    xdg_dirs.get_config_file(CONFIG_FILE_NAME).unwrap()
+}
 
-    let contents = match fs::read_to_string(config_path) {
-        Ok(contents) => contents,
-        Err(error) => {
-            log::error!("Failed to open config: {}", error);
-            return Config::default();
+/// Config files to watch for changes are added to `config_files`.
+/// The error tells why the config isn't loaded, the previous one is kept then.
+fn load_and_apply_config(
+    app: &GpclApp,
+    launcher: &mut Launcher,
+    config_path: &Path,
+    config_files: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    let mut diagnostics = Diagnostics::default();
+
+    let result = match load_config(config_path, &mut diagnostics, config_files) {
+        Some(config) => {
+            apply_config(app, launcher, config, &mut diagnostics);
+            Ok(())
+        }
+        None => {
+            log::warn!("Keep the previous configuration");
+            let error = diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.severity == Severity::Error)
+                .map(|diagnostic| diagnostic.to_string());
+            Err(error.unwrap_or_else(|| "Failed to load the config".to_string()))
         }
     };
 
-    toml::from_str::<Config>(&contents).unwrap_or_else(|error| {
-        log::error!("Failed to parse config: {}", error);
-        Config::default()
-    })
+    set_diagnostics(app, &diagnostics);
+    result
 }
 
-fn load_and_apply_config(app: &GpclApp, launcher: &mut Launcher) {
-    let config = load_config_file();
+fn apply_config(
+    app: &GpclApp,
+    launcher: &mut Launcher,
+    mut config: Config,
+    diagnostics: &mut Diagnostics,
+) {
+    let items = config.take_items();
 
     let layout = app.global::<ScreenLayout>();
-    set_window_layout(&layout, &config.layout.unwrap_or_default());
+    let layout_config = config.layout.unwrap_or_default();
+    set_window_layout(&layout, &layout_config);
 
+    let style_config = config.style.unwrap_or_default();
     let style = app.global::<Style>();
-    set_app_style(&style, &config.style.unwrap_or_default());
+    set_app_style(&style, &style_config, diagnostics);
+
+    let mut icons = IconLookup::new(
+        style_config.icon_theme.as_deref(),
+        layout.get_icon_size(),
+        app.window().scale_factor(),
+    );
+
+    let launch = config.launch.unwrap_or_default();
+    launcher.reset_items(
+        &items,
+        &layout_config,
+        &launch,
+        &config.wrappers,
+        &mut icons,
+        diagnostics,
+    );
+
+    let force_quit = config.force_quit.unwrap_or_default();
+    launcher.set_force_quit_options(&force_quit, diagnostics);
+
+    let kiosk = config.kiosk.unwrap_or_default();
+    launcher.set_kiosk_options(&kiosk, diagnostics);
+}
 
-    launcher.reset_items(&config.items);
+fn set_diagnostics(app: &GpclApp, diagnostics: &Diagnostics) {
+    const MAX_VISIBLE: usize = 5;
+
+    let mut models: Vec<DiagnosticModel> = diagnostics
+        .iter()
+        .take(MAX_VISIBLE)
+        .map(|diagnostic| DiagnosticModel {
+            text: diagnostic.to_string().into(),
+            is_error: diagnostic.severity == Severity::Error,
+        })
+        .collect();
+
+    if diagnostics.len() > MAX_VISIBLE {
+        models.push(DiagnosticModel {
+            text: format!("... and {} more", diagnostics.len() - MAX_VISIBLE).into(),
+            is_error: diagnostics.has_errors(),
+        });
+    }
+
+    app.set_diagnostics(Rc::new(VecModel::from(models)).into());
 }
 
 fn set_window_layout(layout: &ScreenLayout, config: &LayoutConfig) {
+    let mode = match config.mode.unwrap_or_default() {
+        LayoutMode::Row => AppListMode::Row,
+        LayoutMode::Grid => AppListMode::Grid,
+        LayoutMode::List => AppListMode::List,
+    };
+    layout.set_mode(mode);
+
     let default_panel_height = layout.get_default_top_panel_height();
     layout.set_top_panel_height(config.top_panel_height.unwrap_or(default_panel_height));
 
@@ -89,7 +265,7 @@ fn set_window_layout(layout: &ScreenLayout, config: &LayoutConfig) {
     layout.set_icon_size(config.icon_size.unwrap_or(default_icon_size));
 }
 
-fn set_app_style(style: &Style, config: &StyleConfig) {
+fn set_app_style(style: &Style, config: &StyleConfig, diagnostics: &mut Diagnostics) {
     let font_family = config
         .font
         .as_ref()
@@ -102,37 +278,97 @@ fn set_app_style(style: &Style, config: &StyleConfig) {
         .unwrap_or(style.get_default_font_weight());
     style.set_font_weight(font_weight);
 
-    let bg_color = get_color(&config.bg_color).unwrap_or(style.get_default_bg_color());
+    let bg_color = get_color(&config.bg_color, diagnostics).unwrap_or(style.get_default_bg_color());
     style.set_bg_color(bg_color);
 
-    let panel_color = get_color(&config.panel_color).unwrap_or(style.get_default_panel_color());
+    let panel_color =
+        get_color(&config.panel_color, diagnostics).unwrap_or(style.get_default_panel_color());
     style.set_panel_color(panel_color);
 
-    let text_color = get_color(&config.text_color).unwrap_or(style.get_default_text_color());
+    let text_color =
+        get_color(&config.text_color, diagnostics).unwrap_or(style.get_default_text_color());
     style.set_text_color(text_color);
 }
 
-fn get_color(value: &Option<String>) -> Option<Color> {
+fn get_color(value: &Option<String>, diagnostics: &mut Diagnostics) -> Option<Color> {
     let value = value.as_ref()?;
     match HexColor::parse(value) {
         Ok(color) => Some(Color::from_argb_u8(color.a, color.r, color.g, color.b)),
         Err(error) => {
-            log::error!("Failed to parse color `{}`: {}", value, error);
+            let message = format!("Failed to parse color `{}`: {}", value, error);
+            diagnostics.push(Diagnostic::warning(message));
             None
         }
     }
 }
 
-fn setup_config_reloading(app: &GpclApp, launcher: Rc<RefCell<Launcher>>) {
-    let app_weak = app.as_weak();
-    app.on_reload_pressed(move || {
-        if let Some(app) = app_weak.upgrade() {
-            load_and_apply_config(&app, &mut launcher.borrow_mut());
-        }
-    });
+/// Reloads the config, the error tells why the config isn't loaded.
+type ReloadConfig = Rc<dyn Fn() -> Result<(), String>>;
+
+/// Returns the timer watching the config files and the function to reload the config.
+fn setup_config_reloading(
+    app: &GpclApp,
+    launcher: Rc<RefCell<Launcher>>,
+    state: Rc<RefCell<StateStore>>,
+    config_path: PathBuf,
+    config_files: &[PathBuf],
+) -> (Option<Timer>, ReloadConfig) {
+    let drop_in_dirs = [drop_in_dir(&config_path)];
+
+    let config_watcher = ConfigWatcher::new()
+        .map_err(|error| log::error!("{}", error))
+        .ok()
+        .map(|mut watcher| {
+            watcher.watch(config_files, &drop_in_dirs);
+            Rc::new(RefCell::new(watcher))
+        });
+
+    let reload_config: ReloadConfig = {
+        let app_weak = app.as_weak();
+        let config_watcher = config_watcher.clone();
+        Rc::new(move || {
+            let Some(app) = app_weak.upgrade() else {
+                return Ok(());
+            };
+            let mut files = Vec::new();
+            let result =
+                load_and_apply_config(&app, &mut launcher.borrow_mut(), &config_path, &mut files);
+            if let Some(watcher) = &config_watcher {
+                watcher.borrow_mut().watch(&files, &drop_in_dirs);
+            }
+
+            app.invoke_invalidate_app_list();
+            restore_selection(&app, &launcher, &state);
+            result
+        })
+    };
+
+    {
+        let reload_config = reload_config.clone();
+        app.on_reload_pressed(move || _ = reload_config());
+    }
+
+    let Some(config_watcher) = config_watcher else {
+        return (None, reload_config);
+    };
+    let config_watch_timer = Timer::default();
+
+    {
+        let reload_config = reload_config.clone();
+        config_watch_timer.start(TimerMode::Repeated, Duration::from_millis(100), move || {
+            if !config_watcher.borrow_mut().poll() {
+                return;
+            }
+
+            log::info!("Config files are changed, reloading");
+            _ = reload_config();
+        });
+    }
+
+    (Some(config_watch_timer), reload_config)
 }
 
-fn setup_gamepad_manager(app: &GpclApp) -> Timer {
+fn setup_gamepad_manager(app: &GpclApp, launcher: Rc<RefCell<Launcher>>) -> Timer {
     let mut gamepad_manager = GamepadManager::new().unwrap();
     app.set_gamepad_list(gamepad_manager.model().into());
 
@@ -141,7 +377,10 @@ fn setup_gamepad_manager(app: &GpclApp) -> Timer {
 
     gamepad_poll_timer.start(TimerMode::Repeated, Duration::from_millis(16), move || {
         if let Some(app) = app_weak.upgrade() {
-            gamepad_manager.poll(app.window());
+            let force_quit_chord = launcher.borrow().force_quit_chord().clone();
+            if gamepad_manager.poll(app.window(), &force_quit_chord) {
+                launcher.borrow_mut().force_quit();
+            }
         }
     });
 
@@ -168,23 +407,275 @@ fn setup_clock(app: &GpclApp) -> Timer {
     clock_timer
 }
 
-fn setup_launcher(app: &GpclApp, launcher: Rc<RefCell<Launcher>>) -> Timer {
+fn setup_launcher(
+    app: &GpclApp,
+    launcher: Rc<RefCell<Launcher>>,
+    state: Rc<RefCell<StateStore>>,
+) -> Timer {
     app.set_app_list(launcher.borrow().model().into());
 
+    // The list is scrolled to the restored selection once the window is shown
     {
         let launcher = launcher.clone();
-        app.on_app_icon_activated(move |idx| launcher.borrow_mut().exec_item(idx as usize));
+        let state = state.clone();
+        let app_weak = app.as_weak();
+        Timer::single_shot(Duration::ZERO, move || {
+            if let Some(app) = app_weak.upgrade() {
+                restore_selection(&app, &launcher, &state);
+            }
+        });
+    }
+
+    {
+        let launcher = launcher.clone();
+        app.on_selection_changed(move |idx| launcher.borrow_mut().select(idx as usize));
+    }
+
+    {
+        let launcher = launcher.clone();
+        let app_weak = app.as_weak();
+        app.on_app_icon_activated(move |idx| {
+            // Refused launches are logged, the list becomes interactive once it's polled
+            let action = launcher.borrow_mut().exec_item(idx as usize);
+            if let (Ok(Some(action)), Some(app)) = (action, app_weak.upgrade()) {
+                // Nothing is launched, so the list stays interactive
+                app.invoke_set_child_process_state(false);
+                run_app_action(&app, &launcher, action);
+            }
+        });
     }
 
+    {
+        let launcher = launcher.clone();
+        let app_weak = app.as_weak();
+        app.on_back_pressed(move || {
+            let closed_folder = launcher.borrow_mut().close_folder();
+            if let (Some(idx), Some(app)) = (closed_folder, app_weak.upgrade()) {
+                show_folder(&app, &mut launcher.borrow_mut(), idx);
+            }
+        });
+    }
+
+    {
+        let launcher = launcher.clone();
+        let app_weak = app.as_weak();
+        app.on_restart_pressed(move || {
+            if let Some(app) = app_weak.upgrade() {
+                run_app_action(&app, &launcher, AppAction::RestartGpcl);
+            }
+        });
+    }
+    {
+        let launcher = launcher.clone();
+        let app_weak = app.as_weak();
+        app.on_stats_pressed(move || {
+            if let Some(app) = app_weak.upgrade() {
+                run_app_action(&app, &launcher, AppAction::Stats);
+            }
+        });
+    }
+    app.on_quit_pressed(quit);
+
     let app_weak = app.as_weak();
     let child_poll_timer = Timer::default();
+    let mut was_running = false;
 
     child_poll_timer.start(TimerMode::Repeated, Duration::from_millis(250), move || {
         if let Some(app) = app_weak.upgrade() {
-            let is_running = launcher.borrow_mut().check_if_child_is_running();
+            state.borrow_mut().flush();
+
+            let mut launcher = launcher.borrow_mut();
+            launcher.poll_supervisor();
+            launcher.poll_power_actions();
+            let is_running = launcher.check_if_child_is_running();
             app.invoke_set_child_process_state(is_running);
+
+            if was_running && !is_running {
+                app.window().focus();
+
+                // The launch may change the order of the items
+                if launcher.update_order() {
+                    let idx = launcher.restore_selection(state.borrow().selection());
+                    show_folder(&app, &mut launcher, idx);
+                }
+            }
+            was_running = is_running;
+
+            if let Some(failure) = launcher.poll_failure() {
+                app.invoke_show_launch_failure(LaunchFailureModel {
+                    title: format!("{} failed", failure.name).into(),
+                    reason: failure.reason.into(),
+                    output: failure.output.join("\n").into(),
+                });
+            }
         }
     });
 
     child_poll_timer
 }
+
+fn setup_control(
+    app: &GpclApp,
+    launcher: Rc<RefCell<Launcher>>,
+    state: Rc<RefCell<StateStore>>,
+    reload_config: ReloadConfig,
+) -> Option<Timer> {
+    let mut server = ControlServer::bind()
+        .map_err(|error| log::error!("{}", error))
+        .ok()?;
+
+    let app_weak = app.as_weak();
+    let control_timer = Timer::default();
+
+    control_timer.start(TimerMode::Repeated, Duration::from_millis(100), move || {
+        if let Some(app) = app_weak.upgrade() {
+            server
+                .poll(|request| handle_request(&app, &launcher, &state, &*reload_config, request));
+        }
+    });
+
+    Some(control_timer)
+}
+
+fn handle_request(
+    app: &GpclApp,
+    launcher: &RefCell<Launcher>,
+    state: &RefCell<StateStore>,
+    reload_config: &dyn Fn() -> Result<(), String>,
+    request: Request,
+) -> Response {
+    match request {
+        Request::Launch { item } => {
+            let Some(idx) = launcher.borrow_mut().reveal_item(&item) else {
+                return Response::error(format!("Item `{}` is not found", item));
+            };
+
+            // The item is activated like from the list, but without the confirmation
+            let result = launcher.borrow_mut().exec_item(idx);
+            match result {
+                Ok(action) => {
+                    show_folder(app, &mut launcher.borrow_mut(), idx);
+                    if let Some(action) = action {
+                        run_app_action(app, launcher, action);
+                    }
+                    Response::ok()
+                }
+                Err(error) => {
+                    // The saved selection isn't changed yet, so the previous list is shown again
+                    restore_selection(app, launcher, state);
+                    Response::error(error)
+                }
+            }
+        }
+        Request::Reload => match reload_config() {
+            Ok(()) => Response::ok(),
+            Err(error) => Response::error(error),
+        },
+        Request::State => {
+            let launcher = launcher.borrow();
+            let gamepads = app
+                .get_gamepad_list()
+                .iter()
+                .map(|gamepad| GamepadInfo {
+                    name: gamepad.name.to_string(),
+                    status: format!("{:?}", gamepad.status).to_lowercase(),
+                    charge: gamepad.charge,
+                })
+                .collect();
+
+            Response::state(LauncherState {
+                items: launcher.items_info(),
+                selection: state.borrow().selection().clone(),
+                running: launcher.running_item().map(String::from),
+                gamepads,
+            })
+        }
+        Request::Kill => {
+            let mut launcher = launcher.borrow_mut();
+            if launcher.running_item().is_none() {
+                return Response::error("No item is running");
+            }
+            launcher.force_quit();
+            Response::ok()
+        }
+    }
+}
+
+fn run_app_action(app: &GpclApp, launcher: &RefCell<Launcher>, action: AppAction) {
+    match action {
+        AppAction::Reload => app.invoke_reload_pressed(),
+        AppAction::Quit => quit(),
+        AppAction::Settings => app.invoke_show_settings(),
+        AppAction::Stats => show_stats(app),
+        AppAction::OpenFolder(idx) => {
+            let selected = launcher.borrow_mut().open_folder(idx);
+            show_folder(app, &mut launcher.borrow_mut(), selected);
+        }
+        AppAction::RestartGpcl => {
+            launcher.borrow_mut().finish_sessions();
+            let error = restart();
+            log::error!("Failed to restart: {}", error);
+            app.invoke_show_launch_failure(LaunchFailureModel {
+                title: "Restart failed".into(),
+                reason: error.to_string().into(),
+                output: Default::default(),
+            });
+        }
+    }
+}
+
+fn show_stats(app: &GpclApp) {
+    let sessions = play_time::load_sessions().unwrap_or_else(|error| {
+        log::error!("{}", error);
+        Vec::new()
+    });
+    let week_start = play_time::week_start(chrono::Local::now());
+    let rows: Vec<_> = play_time::play_time_by_item(&sessions, week_start)
+        .into_iter()
+        .map(|item| PlayTimeModel {
+            name: item.item.into(),
+            total: play_time::format_duration(item.total).into(),
+            week: format!(
+                "{} ({})",
+                play_time::format_duration(item.week_time),
+                item.week_sessions
+            )
+            .into(),
+            last_played: play_time::format_time(item.last_played).into(),
+        })
+        .collect();
+
+    app.set_play_time(Rc::new(VecModel::from(rows)).into());
+    app.invoke_show_stats();
+}
+
+fn restore_selection(app: &GpclApp, launcher: &RefCell<Launcher>, state: &RefCell<StateStore>) {
+    let idx = launcher
+        .borrow_mut()
+        .restore_selection(state.borrow().selection());
+    show_folder(app, &mut launcher.borrow_mut(), idx);
+}
+
+/// The selection is saved here as the index may stay the same when the list changes,
+/// so the `selection_changed` callback isn't called.
+fn show_folder(app: &GpclApp, launcher: &mut Launcher, selected_idx: usize) {
+    launcher.select(selected_idx);
+    app.set_breadcrumbs(launcher.breadcrumbs().join(" › ").into());
+    app.invoke_select_item(selected_idx as i32);
+}
+
+fn quit() {
+    log::info!("Quit");
+    if let Err(error) = slint::quit_event_loop() {
+        log::error!("Failed to quit: {:?}", error);
+    }
+}
+
+/// Replaces the process with a new gpcl started with the same arguments.
+/// Returns only if it fails.
+fn restart() -> io::Error {
+    log::info!("Restart");
+    let mut args = env::args_os();
+    let program = args.next().unwrap_or_else(|| env!("CARGO_PKG_NAME").into());
+    std::process::Command::new(program).args(args).exec()
+}
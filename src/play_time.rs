@@ -0,0 +1,336 @@
+use crate::cli::StatsFormat;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveTime, TimeZone};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SESSIONS_FILE_NAME: &str = "sessions.jsonl";
+
+/// Time an item was running, Unix times in seconds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub item: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Session {
+    pub fn new(item: &str, start: SystemTime, end: SystemTime) -> Self {
+        Self {
+            item: item.to_string(),
+            start: unix_time(start),
+            end: unix_time(end),
+        }
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// Play time of an item, times are in seconds.
+#[derive(Debug, Default, PartialEq)]
+pub struct ItemPlayTime {
+    pub item: String,
+    pub total: u64,
+    pub last_played: u64,
+    pub week_time: u64,
+    pub week_sessions: usize,
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+fn sessions_path() -> io::Result<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix(env!("CARGO_PKG_NAME"));
+    xdg_dirs.place_state_file(SESSIONS_FILE_NAME)
+}
+
+/// Appends sessions in background, so the event loop doesn't wait for the disk.
+#[derive(Default)]
+pub struct SessionRecorder {
+    tx: Option<Sender<Session>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl SessionRecorder {
+    pub fn record(&mut self, session: Session) {
+        let tx = self.tx.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel();
+            self.writer = Some(thread::spawn(move || {
+                rx.iter().for_each(|session| append_session(&session))
+            }));
+            tx
+        });
+        _ = tx.send(session);
+    }
+
+    /// Waits until the recorded sessions are written.
+    pub fn finish(&mut self) {
+        self.tx = None;
+        if let Some(writer) = self.writer.take() {
+            _ = writer.join();
+        }
+    }
+}
+
+/// Appends the session to `$XDG_STATE_HOME/gpcl/sessions.jsonl`, one JSON object per line.
+fn append_session(session: &Session) {
+    let result = sessions_path().and_then(|path| {
+        let mut line = serde_json::to_string(session).map_err(io::Error::other)?;
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())
+    });
+
+    if let Err(error) = result {
+        log::error!(
+            "Failed to record play time of `{}`: {}",
+            session.item,
+            error
+        );
+    }
+}
+
+/// Loads recorded sessions, lines that can't be parsed are skipped.
+pub fn load_sessions() -> Result<Vec<Session>, String> {
+    let path = sessions_path().map_err(|error| format!("Failed to find sessions: {}", error))?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("Failed to read {}: {}", path.display(), error)),
+    };
+
+    let sessions = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(idx, line)| {
+            serde_json::from_str(line)
+                .map_err(|error| {
+                    log::warn!("{}:{}: {}", path.display(), idx + 1, error);
+                })
+                .ok()
+        })
+        .collect();
+    Ok(sessions)
+}
+
+/// Unix time of the last Monday midnight.
+pub fn week_start(now: DateTime<Local>) -> u64 {
+    let days = Days::new(now.weekday().num_days_from_monday().into());
+    let monday = now.date_naive() - days;
+    Local
+        .from_local_datetime(&monday.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|time| time.timestamp() as u64)
+        .unwrap_or_default()
+}
+
+/// Sums up the sessions per item, the most played items first.
+pub fn play_time_by_item(sessions: &[Session], week_start: u64) -> Vec<ItemPlayTime> {
+    let mut items: HashMap<&str, ItemPlayTime> = HashMap::new();
+    for session in sessions {
+        let item = items
+            .entry(session.item.as_str())
+            .or_insert_with(|| ItemPlayTime {
+                item: session.item.clone(),
+                ..Default::default()
+            });
+
+        item.total += session.duration();
+        item.last_played = item.last_played.max(session.end);
+        if session.start >= week_start {
+            item.week_time += session.duration();
+            item.week_sessions += 1;
+        }
+    }
+
+    let mut items: Vec<_> = items.into_values().collect();
+    items.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.item.cmp(&b.item)));
+    items
+}
+
+pub fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => format!("{}s", seconds),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {:02}m", hours, minutes),
+    }
+}
+
+pub fn format_time(unix_time: u64) -> String {
+    match Local.timestamp_opt(unix_time as i64, 0).earliest() {
+        Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn rfc3339(unix_time: u64) -> String {
+    match Local.timestamp_opt(unix_time as i64, 0).earliest() {
+        Some(time) => time.to_rfc3339(),
+        None => String::new(),
+    }
+}
+
+/// Prints play time of items or recorded sessions, returns false on errors.
+pub fn print_stats(format: StatsFormat) -> bool {
+    let sessions = match load_sessions() {
+        Ok(sessions) => sessions,
+        Err(error) => {
+            eprintln!("{}", error);
+            return false;
+        }
+    };
+
+    match format {
+        StatsFormat::Json => println!("{}", sessions_to_json(&sessions)),
+        StatsFormat::Csv => print!("{}", sessions_to_csv(&sessions)),
+        StatsFormat::Text => {
+            let items = play_time_by_item(&sessions, week_start(Local::now()));
+            if items.is_empty() {
+                println!("No play time recorded yet");
+                return true;
+            }
+
+            let width = items.iter().map(|item| item.item.len()).fold(4, usize::max);
+            println!(
+                "{:width$}  {:>10}  {:>16}  Last played",
+                "Item", "Total", "This week"
+            );
+            for item in &items {
+                let week = format!(
+                    "{} ({})",
+                    format_duration(item.week_time),
+                    item.week_sessions
+                );
+                println!(
+                    "{:width$}  {:>10}  {:>16}  {}",
+                    item.item,
+                    format_duration(item.total),
+                    week,
+                    format_time(item.last_played)
+                );
+            }
+        }
+    }
+    true
+}
+
+#[derive(Serialize)]
+struct ExportedSession<'a> {
+    item: &'a str,
+    start: String,
+    end: String,
+    duration: u64,
+}
+
+/// Sessions as a JSON array with local times and durations in seconds.
+pub fn sessions_to_json(sessions: &[Session]) -> String {
+    let sessions: Vec<_> = sessions
+        .iter()
+        .map(|session| ExportedSession {
+            item: &session.item,
+            start: rfc3339(session.start),
+            end: rfc3339(session.end),
+            duration: session.duration(),
+        })
+        .collect();
+    serde_json::to_string_pretty(&sessions).unwrap_or_default()
+}
+
+/// Sessions as CSV with a header, durations are in seconds.
+pub fn sessions_to_csv(sessions: &[Session]) -> String {
+    let mut csv = String::from("item,start,end,duration\n");
+    for session in sessions {
+        csv += &format!(
+            "{},{},{},{}\n",
+            csv_field(&session.item),
+            rfc3339(session.start),
+            rfc3339(session.end),
+            session.duration()
+        );
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(item: &str, start: u64, end: u64) -> Session {
+        Session {
+            item: item.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn sums_play_time_per_item() {
+        let sessions = [
+            session("Kodi", 100, 400),
+            session("Steam", 500, 600),
+            session("Kodi", 1000, 1100),
+            session("Steam", 2000, 2050),
+        ];
+
+        let expected = [
+            ItemPlayTime {
+                item: "Kodi".into(),
+                total: 400,
+                last_played: 1100,
+                week_time: 100,
+                week_sessions: 1,
+            },
+            ItemPlayTime {
+                item: "Steam".into(),
+                total: 150,
+                last_played: 2050,
+                week_time: 150,
+                week_sessions: 2,
+            },
+        ];
+        assert_eq!(play_time_by_item(&sessions, 500), expected);
+    }
+
+    #[test]
+    fn starts_week_on_monday() {
+        let wednesday = Local.with_ymd_and_hms(2024, 5, 15, 18, 30, 0).unwrap();
+        let monday = Local.with_ymd_and_hms(2024, 5, 13, 0, 0, 0).unwrap();
+        assert_eq!(week_start(wednesday), monday.timestamp() as u64);
+        assert_eq!(week_start(monday), monday.timestamp() as u64);
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(5 * 60 + 10), "5m");
+        assert_eq!(format_duration(2 * 3600 + 7 * 60), "2h 07m");
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!(csv_field("Kodi"), "Kodi");
+        assert_eq!(csv_field("Doom, Heretic"), "\"Doom, Heretic\"");
+        assert_eq!(csv_field("The \"Game\""), "\"The \"\"Game\"\"\"");
+    }
+}
@@ -0,0 +1,199 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const STATE_FILE_NAME: &str = "state.toml";
+
+/// Selected item identified by its name and names of the folders containing it.
+/// The index is used to select a neighbour if the item is removed.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    #[serde(default)]
+    pub folders: Vec<String>,
+    pub item: Option<String>,
+    #[serde(default)]
+    pub index: usize,
+    /// The first folder is the generated "Recent" folder, not a configured one
+    #[serde(default)]
+    pub recent: bool,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LaunchStats {
+    pub count: u32,
+    /// Unix time of the last launch in seconds
+    pub last_launch: u64,
+}
+
+impl LaunchStats {
+    /// Combines statistics of several items, e.g. of a folder.
+    pub fn merge(self, other: LaunchStats) -> Self {
+        Self {
+            count: self.count + other.count,
+            last_launch: self.last_launch.max(other.last_launch),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct State {
+    #[serde(default)]
+    selection: Selection,
+    /// Launch statistics by item name
+    #[serde(default)]
+    launches: BTreeMap<String, LaunchStats>,
+}
+
+/// Launcher state kept between runs in `$XDG_STATE_HOME/gpcl/state.toml`.
+/// Changes are written to disk by `flush`, the default store is kept in memory only.
+#[derive(Default)]
+pub struct StateStore {
+    path: Option<PathBuf>,
+    state: State,
+    changed: bool,
+}
+
+impl StateStore {
+    pub fn open() -> Self {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix(env!("CARGO_PKG_NAME"));
+        let path = xdg_dirs
+            .place_state_file(STATE_FILE_NAME)
+            .map_err(|error| log::error!("Failed to create state directory: {}", error))
+            .ok();
+
+        let state = path.as_deref().map(load_state).unwrap_or_default();
+        Self {
+            path,
+            state,
+            changed: false,
+        }
+    }
+
+    pub fn selection(&self) -> &Selection {
+        &self.state.selection
+    }
+
+    pub fn set_selection(&mut self, selection: Selection) {
+        if self.state.selection != selection {
+            self.state.selection = selection;
+            self.changed = true;
+        }
+    }
+
+    pub fn launches(&self) -> &BTreeMap<String, LaunchStats> {
+        &self.state.launches
+    }
+
+    pub fn record_launch(&mut self, name: &str, time: SystemTime) {
+        let stats = self.state.launches.entry(name.to_string()).or_default();
+        stats.count += 1;
+        stats.last_launch = time
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        self.changed = true;
+    }
+
+    /// Saves the state if it is changed since the last save.
+    pub fn flush(&mut self) {
+        if !self.changed {
+            return;
+        }
+        self.changed = false;
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(error) = save_state(path, &self.state) {
+            log::error!("Failed to save state to {}: {}", path.display(), error);
+        }
+    }
+}
+
+fn load_state(path: &Path) -> State {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => return State::default(),
+        Err(error) => {
+            log::error!("Failed to read state from {}: {}", path.display(), error);
+            return State::default();
+        }
+    };
+
+    toml::from_str(&contents).unwrap_or_else(|error| {
+        log::error!("Failed to parse state from {}: {}", path.display(), error);
+        State::default()
+    })
+}
+
+/// Writes a temporary file and renames it, so the state is not lost if gpcl is killed meanwhile.
+fn save_state(path: &Path, state: &State) -> io::Result<()> {
+    let contents = toml::to_string(state).map_err(io::Error::other)?;
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gpcl-{}-{}.toml", name, std::process::id()))
+    }
+
+    #[test]
+    fn saves_and_loads_state() {
+        let path = temp_path("state");
+        let mut store = StateStore {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+        store.set_selection(Selection {
+            folders: vec!["Games".into()],
+            item: Some("Doom".into()),
+            index: 2,
+            recent: true,
+        });
+        store.record_launch("Doom", UNIX_EPOCH + std::time::Duration::from_secs(100));
+        store.record_launch("Doom", UNIX_EPOCH + std::time::Duration::from_secs(200));
+        store.flush();
+
+        let state = load_state(&path);
+        _ = fs::remove_file(&path);
+
+        assert_eq!(state.selection, store.state.selection);
+        let expected = LaunchStats {
+            count: 2,
+            last_launch: 200,
+        };
+        assert_eq!(state.launches["Doom"], expected);
+    }
+
+    #[test]
+    fn ignores_missing_and_corrupt_state() {
+        let path = temp_path("missing");
+        assert_eq!(load_state(&path).selection, Selection::default());
+
+        let path = temp_path("corrupt");
+        fs::write(&path, "selection = [").unwrap();
+        let state = load_state(&path);
+        _ = fs::remove_file(&path);
+
+        assert_eq!(state.selection, Selection::default());
+        assert!(state.launches.is_empty());
+    }
+
+    #[test]
+    fn fills_missing_fields_with_defaults() {
+        let state: State = toml::from_str("[selection]\nitem = \"Kodi\"").unwrap();
+        let expected = Selection {
+            item: Some("Kodi".into()),
+            ..Default::default()
+        };
+        assert_eq!(state.selection, expected);
+    }
+}
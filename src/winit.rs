@@ -4,6 +4,7 @@ use slint::Window;
 pub trait WinitWindow {
     fn has_focus(&self) -> bool;
     fn hide_cursor(&self);
+    fn focus(&self);
 }
 
 impl WinitWindow for Window {
@@ -14,4 +15,8 @@ impl WinitWindow for Window {
     fn hide_cursor(&self) {
         self.with_winit_window(|ww| ww.set_cursor_visible(false));
     }
+
+    fn focus(&self) {
+        self.with_winit_window(|ww| ww.focus_window());
+    }
 }